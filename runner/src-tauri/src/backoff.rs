@@ -0,0 +1,99 @@
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::Serialize;
+
+use crate::errors::TaskError;
+use crate::tasks::{Task, TaskExecutionConfig};
+
+/// Compute the retry delay for `retry_count` using full jitter:
+/// `random(0, min(retry_delay_ms * 2^retry_count, max_delay_ms))`.
+pub fn compute_delay_ms(config: &TaskExecutionConfig, retry_count: u32) -> u64 {
+    let exponential = config
+        .retry_delay_ms
+        .saturating_mul(1u64 << retry_count.min(32));
+    let capped = exponential.min(config.max_delay_ms);
+    if capped == 0 {
+        return 0;
+    }
+    rand::thread_rng().gen_range(0..=capped)
+}
+
+pub fn next_scheduled_at(config: &TaskExecutionConfig, retry_count: u32) -> DateTime<Utc> {
+    Utc::now() + chrono::Duration::milliseconds(compute_delay_ms(config, retry_count) as i64)
+}
+
+/// Delivery-status-style record of a task that exhausted its retries,
+/// capturing enough context for the dead-letter sink to report on it
+/// without needing to re-read the task.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadLetterReport {
+    pub task_id: String,
+    pub agent_id: String,
+    pub task_type: String,
+    pub final_error: String,
+    pub attempt_count: u32,
+    pub attempt_timestamps: Vec<String>,
+}
+
+/// Sink other subsystems (notifications, activity log, external export)
+/// can implement to consume dead-lettered tasks.
+pub trait DeadLetterSink: Send + Sync {
+    fn handle(&self, report: DeadLetterReport);
+}
+
+/// Keeps dead-lettered reports in memory. A simple default sink until a
+/// richer consumer (e.g. the approval notifier) is wired in.
+#[derive(Default)]
+pub struct InMemoryDeadLetterSink {
+    reports: Mutex<Vec<DeadLetterReport>>,
+}
+
+impl InMemoryDeadLetterSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reports(&self) -> Vec<DeadLetterReport> {
+        self.reports.lock().unwrap().clone()
+    }
+}
+
+impl DeadLetterSink for InMemoryDeadLetterSink {
+    fn handle(&self, report: DeadLetterReport) {
+        self.reports.lock().unwrap().push(report);
+    }
+}
+
+/// What should happen to a task that just failed.
+pub enum RetryOutcome {
+    /// Retry at `scheduled_at` (an RFC 3339 timestamp); the task's status
+    /// should be set back to `Scheduled` and `retry_count` incremented.
+    Retry { scheduled_at: String },
+    /// Retries are exhausted; the task should move to `Failed` and
+    /// `report` should be handed to the dead-letter sink.
+    DeadLetter(DeadLetterReport),
+}
+
+/// Decide whether `task` should retry (with a jittered backoff delay) or be
+/// dead-lettered. `error.is_retryable()` gates retries outright — e.g.
+/// `ApprovalDenied`/`Irrecoverable` dead-letter immediately without
+/// consuming a retry attempt, while `Timeout`/transient `ToolFailure`
+/// retry up to `config.max_retries`.
+pub fn evaluate_failure(task: &Task, config: &TaskExecutionConfig, error: &TaskError) -> RetryOutcome {
+    if error.is_retryable() && task.retry_count < config.max_retries {
+        RetryOutcome::Retry {
+            scheduled_at: next_scheduled_at(config, task.retry_count).to_rfc3339(),
+        }
+    } else {
+        RetryOutcome::DeadLetter(DeadLetterReport {
+            task_id: task.id.clone(),
+            agent_id: task.agent_id.clone(),
+            task_type: task.task_type.clone(),
+            final_error: error.to_legacy_string(),
+            attempt_count: task.retry_count + 1,
+            attempt_timestamps: task.attempt_timestamps.clone(),
+        })
+    }
+}
@@ -0,0 +1,66 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// Lowercase and split on anything that isn't alphanumeric.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Score every document in `corpus` against `query_tokens` using Okapi
+/// BM25 (`k1 = 1.2`, `b = 0.75`), returning `(index, score)` pairs for
+/// documents with a nonzero score, sorted by descending score.
+pub fn rank(query_tokens: &[String], corpus: &[Vec<String>]) -> Vec<(usize, f64)> {
+    let n = corpus.len();
+    if n == 0 || query_tokens.is_empty() {
+        return Vec::new();
+    }
+
+    let avgdl = corpus.iter().map(|doc| doc.len() as f64).sum::<f64>() / n as f64;
+
+    // First pass: document frequency per query term.
+    let idf: HashMap<&str, f64> = query_tokens
+        .iter()
+        .map(|term| {
+            let doc_freq = corpus.iter().filter(|doc| doc.iter().any(|t| t == term)).count() as f64;
+            let value = ((n as f64 - doc_freq + 0.5) / (doc_freq + 0.5) + 1.0).ln();
+            (term.as_str(), value)
+        })
+        .collect();
+
+    // Second pass: score each document against every query term.
+    let mut scored: Vec<(usize, f64)> = corpus
+        .iter()
+        .enumerate()
+        .map(|(i, doc)| {
+            let doc_len = doc.len() as f64;
+            let mut term_freq: HashMap<&str, usize> = HashMap::new();
+            for token in doc {
+                *term_freq.entry(token.as_str()).or_insert(0) += 1;
+            }
+
+            let score: f64 = query_tokens
+                .iter()
+                .map(|term| {
+                    let f = *term_freq.get(term.as_str()).unwrap_or(&0) as f64;
+                    if f == 0.0 {
+                        return 0.0;
+                    }
+                    let term_idf = *idf.get(term.as_str()).unwrap_or(&0.0);
+                    term_idf * (f * (K1 + 1.0)) / (f + K1 * (1.0 - B + B * doc_len / avgdl))
+                })
+                .sum();
+            (i, score)
+        })
+        .filter(|(_, score)| *score > 0.0)
+        .collect();
+
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+    scored
+}
@@ -0,0 +1,277 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::bm25;
+use crate::sanitize_agent_name;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MemoryEntry {
+    pub id: String,
+    pub role: String,
+    pub content: String,
+    pub timestamp: String,
+    #[serde(rename = "conversationId")]
+    pub conversation_id: String,
+}
+
+#[derive(Serialize)]
+pub struct MemoryMatch {
+    pub content: String,
+    pub role: String,
+    pub timestamp: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub action: String,
+    pub detail: String,
+    pub timestamp: String,
+    #[serde(rename = "conversationId")]
+    pub conversation_id: String,
+}
+
+/// Persistence backend for a single agent's brain folder: memory, audit,
+/// and saved conversations. `FileBrainStore` keeps the original flat
+/// JSONL-on-disk layout; `SqliteBrainStore` indexes the same data in
+/// SQLite (with FTS for memory search) for brains that have outgrown a
+/// line-by-line scan.
+pub trait BrainStore: Send + Sync {
+    fn append_memory(&self, agent_name: &str, entry: &MemoryEntry) -> Result<(), String>;
+    fn query_memory(&self, agent_name: &str, query: &str, limit: usize) -> Result<Vec<MemoryMatch>, String>;
+    fn append_audit(&self, agent_name: &str, entry: &AuditEntry) -> Result<(), String>;
+    fn save_conversation(&self, agent_name: &str, date_folder: &str, file_name: &str, contents: &str) -> Result<(), String>;
+}
+
+/// The original layout: `<brain_root>/AgentForge Brain/<agent>/{memory,audit,conversations}/...`.
+pub struct FileBrainStore {
+    brain_root: PathBuf,
+}
+
+impl FileBrainStore {
+    pub fn new(brain_root: PathBuf) -> Self {
+        Self { brain_root }
+    }
+
+    fn agent_dir(&self, agent_name: &str) -> PathBuf {
+        self.brain_root.join("AgentForge Brain").join(sanitize_agent_name(agent_name))
+    }
+}
+
+impl BrainStore for FileBrainStore {
+    fn append_memory(&self, agent_name: &str, entry: &MemoryEntry) -> Result<(), String> {
+        let target = self.agent_dir(agent_name).join("memory");
+        fs::create_dir_all(&target).map_err(|e| format!("Failed to create memory folder: {e}"))?;
+        let file_path = target.join("memory.jsonl");
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file_path)
+            .map_err(|e| format!("Failed to open memory file: {e}"))?;
+        let line = serde_json::to_string(entry).map_err(|e| format!("Failed to serialize entry: {e}"))?;
+        use std::io::Write;
+        writeln!(file, "{line}").map_err(|e| format!("Failed to write memory entry: {e}"))
+    }
+
+    fn query_memory(&self, agent_name: &str, query: &str, limit: usize) -> Result<Vec<MemoryMatch>, String> {
+        let file_path = self.agent_dir(agent_name).join("memory").join("memory.jsonl");
+        let contents = fs::read_to_string(&file_path).unwrap_or_default();
+        let query_tokens = bm25::tokenize(query);
+        if query_tokens.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let entries: Vec<MemoryEntry> = contents
+            .lines()
+            .filter_map(|line| serde_json::from_str::<MemoryEntry>(line).ok())
+            .collect();
+        let corpus: Vec<Vec<String>> = entries.iter().map(|entry| bm25::tokenize(&entry.content)).collect();
+
+        let ranked = bm25::rank(&query_tokens, &corpus);
+        let results = ranked
+            .into_iter()
+            .take(limit.max(1))
+            .map(|(i, _)| {
+                let entry = &entries[i];
+                MemoryMatch {
+                    content: entry.content.clone(),
+                    role: entry.role.clone(),
+                    timestamp: entry.timestamp.clone(),
+                }
+            })
+            .collect();
+        Ok(results)
+    }
+
+    fn append_audit(&self, agent_name: &str, entry: &AuditEntry) -> Result<(), String> {
+        let target = self.agent_dir(agent_name).join("audit");
+        fs::create_dir_all(&target).map_err(|e| format!("Failed to create audit folder: {e}"))?;
+        let file_path = target.join("audit.jsonl");
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file_path)
+            .map_err(|e| format!("Failed to open audit file: {e}"))?;
+        let line = serde_json::to_string(entry).map_err(|e| format!("Failed to serialize entry: {e}"))?;
+        use std::io::Write;
+        writeln!(file, "{line}").map_err(|e| format!("Failed to write audit entry: {e}"))
+    }
+
+    fn save_conversation(&self, agent_name: &str, date_folder: &str, file_name: &str, contents: &str) -> Result<(), String> {
+        let target = self.agent_dir(agent_name).join("conversations").join(date_folder);
+        fs::create_dir_all(&target).map_err(|e| format!("Failed to create brain folder: {e}"))?;
+        let file_path = target.join(file_name);
+        fs::write(&file_path, contents).map_err(|e| format!("Failed to write brain file: {e}"))
+    }
+}
+
+/// SQLite-backed store. Tables are per-brain (one database file at
+/// `<brain_root>/AgentForge Brain/brain.db`, rows scoped by `agent_name`),
+/// with an FTS5 index over memory content so `query_memory` doesn't need
+/// to rescan every entry.
+pub struct SqliteBrainStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteBrainStore {
+    pub fn new(brain_root: &Path) -> Result<Self, String> {
+        let dir = brain_root.join("AgentForge Brain");
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create brain folder: {e}"))?;
+        let conn = Connection::open(dir.join("brain.db")).map_err(|e| format!("Failed to open brain database: {e}"))?;
+        let store = Self { conn: Mutex::new(conn) };
+        store.initialize()?;
+        Ok(store)
+    }
+
+    fn initialize(&self) -> Result<(), String> {
+        let conn = self.conn.lock().map_err(|_| "brain database lock poisoned".to_string())?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS memory (
+                id TEXT PRIMARY KEY,
+                agent_name TEXT NOT NULL,
+                role TEXT NOT NULL,
+                content TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                conversation_id TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| format!("Failed to create memory table: {e}"))?;
+        conn.execute(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS memory_fts USING fts5(
+                content, content='memory', content_rowid='rowid'
+            )",
+            [],
+        )
+        .map_err(|e| format!("Failed to create memory FTS index: {e}"))?;
+        conn.execute(
+            "CREATE TRIGGER IF NOT EXISTS memory_ai AFTER INSERT ON memory BEGIN
+                INSERT INTO memory_fts(rowid, content) VALUES (new.rowid, new.content);
+            END",
+            [],
+        )
+        .map_err(|e| format!("Failed to create memory FTS trigger: {e}"))?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS audit (
+                agent_name TEXT NOT NULL,
+                action TEXT NOT NULL,
+                detail TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                conversation_id TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| format!("Failed to create audit table: {e}"))?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS conversations (
+                agent_name TEXT NOT NULL,
+                date_folder TEXT NOT NULL,
+                file_name TEXT NOT NULL,
+                contents TEXT NOT NULL,
+                PRIMARY KEY (agent_name, date_folder, file_name)
+            )",
+            [],
+        )
+        .map_err(|e| format!("Failed to create conversations table: {e}"))?;
+        Ok(())
+    }
+}
+
+impl BrainStore for SqliteBrainStore {
+    fn append_memory(&self, agent_name: &str, entry: &MemoryEntry) -> Result<(), String> {
+        let conn = self.conn.lock().map_err(|_| "brain database lock poisoned".to_string())?;
+        conn.execute(
+            "INSERT INTO memory (id, agent_name, role, content, timestamp, conversation_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![entry.id, agent_name, entry.role, entry.content, entry.timestamp, entry.conversation_id],
+        )
+        .map_err(|e| format!("Failed to insert memory entry: {e}"))?;
+        Ok(())
+    }
+
+    fn query_memory(&self, agent_name: &str, query: &str, limit: usize) -> Result<Vec<MemoryMatch>, String> {
+        let conn = self.conn.lock().map_err(|_| "brain database lock poisoned".to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT memory.role, memory.content, memory.timestamp
+                 FROM memory_fts
+                 JOIN memory ON memory.rowid = memory_fts.rowid
+                 WHERE memory.agent_name = ?1 AND memory_fts MATCH ?2
+                 ORDER BY bm25(memory_fts)
+                 LIMIT ?3",
+            )
+            .map_err(|e| format!("Failed to prepare memory query: {e}"))?;
+        let rows = stmt
+            .query_map(params![agent_name, query, limit.max(1) as i64], |row| {
+                Ok(MemoryMatch {
+                    role: row.get(0)?,
+                    content: row.get(1)?,
+                    timestamp: row.get(2)?,
+                })
+            })
+            .map_err(|e| format!("Failed to run memory query: {e}"))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(|e| format!("Failed to read memory results: {e}"))
+    }
+
+    fn append_audit(&self, agent_name: &str, entry: &AuditEntry) -> Result<(), String> {
+        let conn = self.conn.lock().map_err(|_| "brain database lock poisoned".to_string())?;
+        conn.execute(
+            "INSERT INTO audit (agent_name, action, detail, timestamp, conversation_id) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![agent_name, entry.action, entry.detail, entry.timestamp, entry.conversation_id],
+        )
+        .map_err(|e| format!("Failed to insert audit entry: {e}"))?;
+        Ok(())
+    }
+
+    fn save_conversation(&self, agent_name: &str, date_folder: &str, file_name: &str, contents: &str) -> Result<(), String> {
+        let conn = self.conn.lock().map_err(|_| "brain database lock poisoned".to_string())?;
+        conn.execute(
+            "INSERT INTO conversations (agent_name, date_folder, file_name, contents) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(agent_name, date_folder, file_name) DO UPDATE SET contents = excluded.contents",
+            params![agent_name, date_folder, file_name, contents],
+        )
+        .map_err(|e| format!("Failed to upsert conversation: {e}"))?;
+        Ok(())
+    }
+}
+
+/// Pick the backend named by `backend`, as read by `brain_backend_setting`
+/// (`"sqlite"` or, by default, `"file"`).
+pub fn build_brain_store(brain_root: PathBuf, backend: Option<&str>) -> Result<Box<dyn BrainStore>, String> {
+    match backend {
+        Some("sqlite") => Ok(Box::new(SqliteBrainStore::new(&brain_root)?)),
+        _ => Ok(Box::new(FileBrainStore::new(brain_root))),
+    }
+}
+
+/// Read the `"backend"` field out of `<brain_root>/AgentForge Brain/brain-settings.json`,
+/// the brain folder's own (optional) settings file. Missing or unparsable
+/// settings fall back to the default `FileBrainStore`.
+pub fn brain_backend_setting(brain_root: &Path) -> Option<String> {
+    let path = brain_root.join("AgentForge Brain").join("brain-settings.json");
+    let contents = fs::read_to_string(path).ok()?;
+    let value: serde_json::Value = serde_json::from_str(&contents).ok()?;
+    value.get("backend").and_then(|v| v.as_str()).map(str::to_string)
+}
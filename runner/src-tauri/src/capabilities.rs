@@ -0,0 +1,133 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// A single fine-grained grant, e.g. `"local_files:read"` or
+/// `"terminal:exec:git"` — checked against the specific action a
+/// dispatched tool call is about to take, not just whether the tool
+/// itself is enabled.
+pub type Permission = String;
+
+/// A named bundle of permissions bound to an agent (or every agent, when
+/// `agent_name` is `None`). Mirrors Tauri's own capability/permission
+/// split: capabilities are the unit a user grants, permissions are the
+/// unit a tool call is checked against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Capability {
+    pub id: String,
+    pub name: String,
+    pub agent_name: Option<String>,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CapabilityConfig {
+    pub capabilities: Vec<Capability>,
+}
+
+/// Capabilities are persisted next to `mcp-settings.json`, under the same
+/// app-data directory.
+fn capabilities_path(settings_path: &Path) -> PathBuf {
+    settings_path.with_file_name("mcp-capabilities.json")
+}
+
+pub fn read_capabilities(settings_path: &Path) -> CapabilityConfig {
+    let path = capabilities_path(settings_path);
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    serde_json::from_str(&contents).unwrap_or_default()
+}
+
+fn write_capabilities(settings_path: &Path, config: &CapabilityConfig) -> Result<(), String> {
+    let path = capabilities_path(settings_path);
+    let json = serde_json::to_string_pretty(config).map_err(|e| format!("Failed to serialize capabilities: {e}"))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write capabilities: {e}"))
+}
+
+/// The permissions granted to `agent_name`: the union of every capability
+/// bound to that agent plus every capability bound to all agents (`None`).
+pub fn resolved_permissions(config: &CapabilityConfig, agent_name: &str) -> Vec<Permission> {
+    let mut granted: Vec<Permission> = config
+        .capabilities
+        .iter()
+        .filter(|cap| cap.agent_name.as_deref().map_or(true, |a| a == agent_name))
+        .flat_map(|cap| cap.permissions.iter().cloned())
+        .collect();
+    granted.sort();
+    granted.dedup();
+    granted
+}
+
+/// Derive the permission a tool call needs from its name and arguments, so
+/// the dispatcher can check it against the caller's resolved permission
+/// set before running it. `None` means this tool has no finer-grained
+/// permission of its own (e.g. it's not implemented yet).
+pub fn required_permission(tool_name: &str, arguments: &Value) -> Option<Permission> {
+    match tool_name {
+        "local_files" => match arguments.get("action").and_then(|v| v.as_str()) {
+            Some("read") => Some("local_files:read".to_string()),
+            Some("write") => Some("local_files:write".to_string()),
+            _ => None,
+        },
+        "terminal" => {
+            let command = arguments.get("command").and_then(|v| v.as_str()).unwrap_or("");
+            Some(format!("terminal:exec:{command}"))
+        }
+        "browser" => Some("browser:navigate".to_string()),
+        _ => None,
+    }
+}
+
+pub fn create_capability(
+    settings_path: &Path,
+    name: String,
+    agent_name: Option<String>,
+    permissions: Vec<Permission>,
+) -> Result<Capability, String> {
+    let mut config = read_capabilities(settings_path);
+    let capability = Capability {
+        id: Uuid::new_v4().to_string(),
+        name,
+        agent_name,
+        permissions,
+    };
+    config.capabilities.push(capability.clone());
+    write_capabilities(settings_path, &config)?;
+    Ok(capability)
+}
+
+pub fn add_permission(settings_path: &Path, capability_id: &str, permission: Permission) -> Result<(), String> {
+    let mut config = read_capabilities(settings_path);
+    let capability = config
+        .capabilities
+        .iter_mut()
+        .find(|c| c.id == capability_id)
+        .ok_or_else(|| format!("Unknown capability '{capability_id}'"))?;
+    if !capability.permissions.contains(&permission) {
+        capability.permissions.push(permission);
+    }
+    write_capabilities(settings_path, &config)
+}
+
+pub fn remove_permission(settings_path: &Path, capability_id: &str, permission: &str) -> Result<(), String> {
+    let mut config = read_capabilities(settings_path);
+    let capability = config
+        .capabilities
+        .iter_mut()
+        .find(|c| c.id == capability_id)
+        .ok_or_else(|| format!("Unknown capability '{capability_id}'"))?;
+    capability.permissions.retain(|p| p != permission);
+    write_capabilities(settings_path, &config)
+}
+
+pub fn list_permissions(settings_path: &Path, capability_id: &str) -> Result<Vec<Permission>, String> {
+    let config = read_capabilities(settings_path);
+    config
+        .capabilities
+        .iter()
+        .find(|c| c.id == capability_id)
+        .map(|c| c.permissions.clone())
+        .ok_or_else(|| format!("Unknown capability '{capability_id}'"))
+}
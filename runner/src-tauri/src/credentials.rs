@@ -1,27 +1,103 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
 use keyring::Entry;
+use rand::RngCore;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 
 const SERVICE_PREFIX: &str = "agentforge";
 
-/// Store a credential in the system keychain
+/// Which backend `store_credential`/`get_credential`/`delete_credential`
+/// use. `Auto` (the default) prefers the OS keyring and only stores into
+/// the encrypted file vault when the keyring itself errors — e.g. on
+/// headless Linux/CI/containers with no secret service running. Lookups in
+/// `Auto` mode also check the vault when the keyring has no entry, so a
+/// value stored there during a keyring outage is still found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialBackend {
+    Auto,
+    KeyringOnly,
+    VaultOnly,
+}
+
+static BACKEND: OnceLock<Mutex<CredentialBackend>> = OnceLock::new();
+
+fn backend_cell() -> &'static Mutex<CredentialBackend> {
+    BACKEND.get_or_init(|| Mutex::new(CredentialBackend::Auto))
+}
+
+pub fn credential_backend() -> CredentialBackend {
+    *backend_cell().lock().unwrap()
+}
+
+pub fn set_credential_backend(backend: CredentialBackend) {
+    *backend_cell().lock().unwrap() = backend;
+}
+
+/// Store a credential in the system keychain, or the encrypted vault
+/// fallback depending on `credential_backend()`.
 pub fn store_credential(service: &str, key: &str, value: &str) -> Result<(), String> {
+    match credential_backend() {
+        CredentialBackend::VaultOnly => vault::store(service, key, value),
+        CredentialBackend::KeyringOnly => keyring_store(service, key, value),
+        CredentialBackend::Auto => keyring_store(service, key, value).or_else(|_| vault::store(service, key, value)),
+    }
+}
+
+/// Retrieve a credential from the system keychain, or the encrypted vault
+/// fallback depending on `credential_backend()`.
+pub fn get_credential(service: &str, key: &str) -> Result<Option<String>, String> {
+    match credential_backend() {
+        CredentialBackend::VaultOnly => vault::get(service, key),
+        CredentialBackend::KeyringOnly => keyring_get(service, key),
+        CredentialBackend::Auto => match keyring_get(service, key) {
+            Ok(Some(value)) => Ok(Some(value)),
+            Ok(None) => vault::get(service, key),
+            Err(_) => vault::get(service, key),
+        },
+    }
+}
+
+/// Delete a credential from the system keychain, or the encrypted vault
+/// fallback depending on `credential_backend()`. In `Auto` mode both
+/// stores are cleared, since a value stored while the keyring was broken
+/// and later restored could otherwise leave a stale copy behind.
+pub fn delete_credential(service: &str, key: &str) -> Result<(), String> {
+    match credential_backend() {
+        CredentialBackend::VaultOnly => vault::delete(service, key),
+        CredentialBackend::KeyringOnly => keyring_delete(service, key),
+        CredentialBackend::Auto => {
+            let keyring_result = keyring_delete(service, key);
+            let vault_result = vault::delete(service, key);
+            keyring_result.or(vault_result)
+        }
+    }
+}
+
+fn keyring_store(service: &str, key: &str, value: &str) -> Result<(), String> {
     let full_service = format!("{}-{}", SERVICE_PREFIX, service);
-    
+
     let entry = Entry::new(&full_service, key)
         .map_err(|e| format!("Failed to create keyring entry: {}", e))?;
-    
+
     entry.set_password(value)
         .map_err(|e| format!("Failed to store credential: {}", e))?;
-    
+
     Ok(())
 }
 
-/// Retrieve a credential from the system keychain
-pub fn get_credential(service: &str, key: &str) -> Result<Option<String>, String> {
+fn keyring_get(service: &str, key: &str) -> Result<Option<String>, String> {
     let full_service = format!("{}-{}", SERVICE_PREFIX, service);
-    
+
     let entry = Entry::new(&full_service, key)
         .map_err(|e| format!("Failed to create keyring entry: {}", e))?;
-    
+
     match entry.get_password() {
         Ok(password) => Ok(Some(password)),
         Err(keyring::Error::NoEntry) => Ok(None),
@@ -29,13 +105,12 @@ pub fn get_credential(service: &str, key: &str) -> Result<Option<String>, String
     }
 }
 
-/// Delete a credential from the system keychain
-pub fn delete_credential(service: &str, key: &str) -> Result<(), String> {
+fn keyring_delete(service: &str, key: &str) -> Result<(), String> {
     let full_service = format!("{}-{}", SERVICE_PREFIX, service);
-    
+
     let entry = Entry::new(&full_service, key)
         .map_err(|e| format!("Failed to create keyring entry: {}", e))?;
-    
+
     match entry.delete_credential() {
         Ok(_) => Ok(()),
         Err(keyring::Error::NoEntry) => Ok(()), // Already deleted
@@ -43,67 +118,351 @@ pub fn delete_credential(service: &str, key: &str) -> Result<(), String> {
     }
 }
 
-/// List of credential types for tools
-#[derive(Debug, Clone)]
-pub struct CredentialType {
+/// The encrypted on-disk fallback used when the OS keyring errors out
+/// entirely. One JSON file holds every `{service, key, nonce, ciphertext}`
+/// record, encrypted with XChaCha20-Poly1305 under a key derived (via
+/// Argon2id) from a master secret that itself lives in the keyring when
+/// possible, falling back to a restricted-permission local file when even
+/// that one keyring write fails.
+mod vault {
+    use super::*;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct VaultRecord {
+        service: String,
+        key: String,
+        nonce: String,
+        ciphertext: String,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct VaultFile {
+        salt: String,
+        records: Vec<VaultRecord>,
+    }
+
+    impl Default for VaultFile {
+        fn default() -> Self {
+            Self {
+                salt: hex_encode(&random_bytes::<16>()),
+                records: Vec::new(),
+            }
+        }
+    }
+
+    fn vault_dir() -> Result<PathBuf, String> {
+        std::env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(".agentforge").join("vault"))
+            .ok_or_else(|| "could not resolve home directory for credential vault".to_string())
+    }
+
+    fn vault_file_path() -> Result<PathBuf, String> {
+        vault_dir().map(|dir| dir.join("credentials.json"))
+    }
+
+    fn random_bytes<const N: usize>() -> [u8; N] {
+        let mut bytes = [0u8; N];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        bytes
+    }
+
+    fn hex_encode(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn hex_decode(s: &str) -> Option<Vec<u8>> {
+        if s.len() % 2 != 0 {
+            return None;
+        }
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+            .collect()
+    }
+
+    fn write_restricted(path: &std::path::Path, contents: &str) -> Result<(), String> {
+        fs::write(path, contents).map_err(|e| format!("failed to write credential vault: {e}"))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = fs::set_permissions(path, fs::Permissions::from_mode(0o600));
+        }
+        Ok(())
+    }
+
+    /// The symmetric secret everything else is derived from. Prefers a
+    /// random secret stored in the OS keyring; if the keyring can't even
+    /// hold that one entry (the same failure mode driving callers to the
+    /// vault in the first place), persists it to a `0600` local file
+    /// instead so the vault still works headless.
+    fn master_secret() -> Result<Vec<u8>, String> {
+        let vault_service = format!("{}-vault", SERVICE_PREFIX);
+        if let Ok(entry) = Entry::new(&vault_service, "master-key") {
+            match entry.get_password() {
+                Ok(hex_secret) => {
+                    return hex_decode(&hex_secret)
+                        .ok_or_else(|| "credential vault master key is corrupt".to_string());
+                }
+                Err(keyring::Error::NoEntry) => {
+                    let secret = random_bytes::<32>();
+                    if entry.set_password(&hex_encode(&secret)).is_ok() {
+                        return Ok(secret.to_vec());
+                    }
+                }
+                Err(_) => {}
+            }
+        }
+
+        let dir = vault_dir()?;
+        fs::create_dir_all(&dir).map_err(|e| format!("failed to create credential vault dir: {e}"))?;
+        let key_path = dir.join("master.key");
+        if let Ok(existing) = fs::read_to_string(&key_path) {
+            return hex_decode(existing.trim())
+                .ok_or_else(|| "credential vault master key file is corrupt".to_string());
+        }
+        let secret = random_bytes::<32>();
+        write_restricted(&key_path, &hex_encode(&secret))?;
+        Ok(secret.to_vec())
+    }
+
+    fn derive_key(salt: &[u8]) -> Result<Key, String> {
+        let secret = master_secret()?;
+        let mut key_bytes = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(&secret, salt, &mut key_bytes)
+            .map_err(|e| format!("credential vault key derivation failed: {e}"))?;
+        Ok(*Key::from_slice(&key_bytes))
+    }
+
+    fn load() -> Result<VaultFile, String> {
+        let path = vault_file_path()?;
+        match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| format!("credential vault file is corrupt: {e}")),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(VaultFile::default()),
+            Err(e) => Err(format!("failed to read credential vault: {e}")),
+        }
+    }
+
+    fn save(vault: &VaultFile) -> Result<(), String> {
+        let dir = vault_dir()?;
+        fs::create_dir_all(&dir).map_err(|e| format!("failed to create credential vault dir: {e}"))?;
+        let contents = serde_json::to_string_pretty(vault)
+            .map_err(|e| format!("failed to serialize credential vault: {e}"))?;
+        write_restricted(&vault_file_path()?, &contents)
+    }
+
+    pub fn store(service: &str, key: &str, value: &str) -> Result<(), String> {
+        let mut vault = load()?;
+        let salt = hex_decode(&vault.salt).ok_or("credential vault salt is corrupt")?;
+        let cipher_key = derive_key(&salt)?;
+
+        let cipher = XChaCha20Poly1305::new(&cipher_key);
+        let nonce_bytes = random_bytes::<24>();
+        let nonce = XNonce::from_slice(&nonce_bytes);
+        let ciphertext = cipher
+            .encrypt(nonce, value.as_bytes())
+            .map_err(|e| format!("failed to encrypt credential: {e}"))?;
+
+        vault.records.retain(|r| !(r.service == service && r.key == key));
+        vault.records.push(VaultRecord {
+            service: service.to_string(),
+            key: key.to_string(),
+            nonce: hex_encode(&nonce_bytes),
+            ciphertext: hex_encode(&ciphertext),
+        });
+        save(&vault)
+    }
+
+    pub fn get(service: &str, key: &str) -> Result<Option<String>, String> {
+        let vault = load()?;
+        let Some(record) = vault.records.iter().find(|r| r.service == service && r.key == key) else {
+            return Ok(None);
+        };
+
+        let salt = hex_decode(&vault.salt).ok_or("credential vault salt is corrupt")?;
+        let cipher_key = derive_key(&salt)?;
+        let cipher = XChaCha20Poly1305::new(&cipher_key);
+
+        let nonce_bytes = hex_decode(&record.nonce).ok_or("credential vault entry nonce is corrupt")?;
+        let ciphertext = hex_decode(&record.ciphertext).ok_or("credential vault entry ciphertext is corrupt")?;
+        let nonce = XNonce::from_slice(&nonce_bytes);
+
+        let plaintext = cipher
+            .decrypt(nonce, ciphertext.as_ref())
+            .map_err(|_| "credential vault entry failed authentication (corrupt or tampered)".to_string())?;
+        String::from_utf8(plaintext)
+            .map(Some)
+            .map_err(|_| "credential vault entry decrypted to invalid UTF-8".to_string())
+    }
+
+    pub fn delete(service: &str, key: &str) -> Result<(), String> {
+        let mut vault = load()?;
+        vault.records.retain(|r| !(r.service == service && r.key == key));
+        save(&vault)
+    }
+}
+
+/// Bundled defaults for `registry::load`, in the same shape a user's
+/// override manifest takes. Edit `credential_types.default.json` to add a
+/// built-in tool rather than growing a Rust `match`.
+const DEFAULT_MANIFEST: &str = include_str!("credential_types.default.json");
+
+/// A typed field a `CredentialTypeDefinition` requires, e.g. `api_key` as
+/// a required `secret`, or a `region` as an `enum` of allowed values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialField {
+    pub name: String,
+    #[serde(flatten)]
+    pub field_type: CredentialFieldType,
+    #[serde(default = "default_required")]
+    pub required: bool,
+    /// Checked against the field's value (after the `required`/`enum`
+    /// checks already pass) when present.
+    #[serde(default)]
+    pub validation_regex: Option<String>,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CredentialFieldType {
+    String,
+    Secret,
+    OauthToken,
+    Url,
+    Enum { allowed: Vec<String> },
+}
+
+/// Descriptor for a tool's OAuth authorization-code flow, so the UI can
+/// drive it without the field types themselves encoding the flow.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthFlowDescriptor {
+    pub authorize_url: String,
+    pub token_url: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+/// One entry in the credential type registry, replacing the old
+/// hard-coded `match` in `get_tool_credential_type` with data loaded from
+/// `DEFAULT_MANIFEST` plus a user-editable override file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialTypeDefinition {
     pub id: String,
     pub name: String,
-    pub required_fields: Vec<String>,
-}
-
-/// Get credential requirements for a tool
-pub fn get_tool_credential_type(tool_id: &str) -> Option<CredentialType> {
-    match tool_id {
-        "openai" => Some(CredentialType {
-            id: "openai".to_string(),
-            name: "OpenAI".to_string(),
-            required_fields: vec!["api_key".to_string()],
-        }),
-        "anthropic" => Some(CredentialType {
-            id: "anthropic".to_string(),
-            name: "Anthropic".to_string(),
-            required_fields: vec!["api_key".to_string()],
-        }),
-        "google" => Some(CredentialType {
-            id: "google".to_string(),
-            name: "Google AI".to_string(),
-            required_fields: vec!["api_key".to_string()],
-        }),
-        "groq" => Some(CredentialType {
-            id: "groq".to_string(),
-            name: "Groq".to_string(),
-            required_fields: vec!["api_key".to_string()],
-        }),
-        "xai" => Some(CredentialType {
-            id: "xai".to_string(),
-            name: "xAI".to_string(),
-            required_fields: vec!["api_key".to_string()],
-        }),
-        "gmail" => Some(CredentialType {
-            id: "gmail".to_string(),
-            name: "Gmail".to_string(),
-            required_fields: vec!["client_id".to_string(), "client_secret".to_string(), "refresh_token".to_string()],
-        }),
-        "tavily" => Some(CredentialType {
-            id: "tavily".to_string(),
-            name: "Tavily Search".to_string(),
-            required_fields: vec!["api_key".to_string()],
-        }),
-        "serpapi" => Some(CredentialType {
-            id: "serpapi".to_string(),
-            name: "SerpAPI".to_string(),
-            required_fields: vec!["api_key".to_string()],
-        }),
-        "alpha_vantage" => Some(CredentialType {
-            id: "alpha_vantage".to_string(),
-            name: "Alpha Vantage".to_string(),
-            required_fields: vec!["api_key".to_string()],
-        }),
-        "newsapi" => Some(CredentialType {
-            id: "newsapi".to_string(),
-            name: "NewsAPI".to_string(),
-            required_fields: vec!["api_key".to_string()],
-        }),
-        _ => None,
+    pub fields: Vec<CredentialField>,
+    #[serde(default)]
+    pub oauth: Option<OAuthFlowDescriptor>,
+}
+
+/// Registry of known `CredentialTypeDefinition`s: the bundled
+/// `DEFAULT_MANIFEST` overlaid with a user-editable manifest under the
+/// app data dir, keyed by `id` so an override either replaces a built-in
+/// definition or adds a brand new tool.
+mod registry {
+    use super::*;
+
+    fn override_path() -> Option<PathBuf> {
+        std::env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(".agentforge").join("credential_types.json"))
+    }
+
+    pub fn load() -> Vec<CredentialTypeDefinition> {
+        let mut definitions: Vec<CredentialTypeDefinition> =
+            serde_json::from_str(DEFAULT_MANIFEST).unwrap_or_default();
+
+        let Some(path) = override_path() else {
+            return definitions;
+        };
+        let Ok(contents) = fs::read_to_string(path) else {
+            return definitions;
+        };
+        let Ok(overrides) = serde_json::from_str::<Vec<CredentialTypeDefinition>>(&contents) else {
+            return definitions;
+        };
+
+        for over in overrides {
+            match definitions.iter_mut().find(|d| d.id == over.id) {
+                Some(existing) => *existing = over,
+                None => definitions.push(over),
+            }
+        }
+        definitions
+    }
+
+    pub fn save_override(definition: CredentialTypeDefinition) -> Result<(), String> {
+        let path = override_path().ok_or("could not resolve home directory for credential type registry")?;
+        let mut overrides: Vec<CredentialTypeDefinition> = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+
+        overrides.retain(|d| d.id != definition.id);
+        overrides.push(definition);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("failed to create credential type registry dir: {e}"))?;
+        }
+        let contents = serde_json::to_string_pretty(&overrides)
+            .map_err(|e| format!("failed to serialize credential types: {e}"))?;
+        fs::write(&path, contents).map_err(|e| format!("failed to write credential type registry: {e}"))
+    }
+}
+
+/// All known credential types: the bundled defaults plus any
+/// user-registered overrides/additions.
+pub fn list_credential_types() -> Vec<CredentialTypeDefinition> {
+    registry::load()
+}
+
+/// Add (or replace, by `id`) a credential type in the user-editable
+/// override manifest, on top of the bundled defaults.
+pub fn register_credential_type(definition: CredentialTypeDefinition) -> Result<(), String> {
+    registry::save_override(definition)
+}
+
+/// Get credential requirements for a tool.
+pub fn get_tool_credential_type(tool_id: &str) -> Option<CredentialTypeDefinition> {
+    registry::load().into_iter().find(|d| d.id == tool_id)
+}
+
+/// Check `values` (field name -> entered value) against `tool_id`'s
+/// `CredentialTypeDefinition` before `store_credential` is allowed to run:
+/// every required field must be present and non-empty, `enum` fields must
+/// match one of their `allowed` values, and any `validation_regex` must
+/// match.
+pub fn validate_credential(tool_id: &str, values: &HashMap<String, String>) -> Result<(), String> {
+    let definition = get_tool_credential_type(tool_id)
+        .ok_or_else(|| format!("unknown credential type '{tool_id}'"))?;
+
+    for field in &definition.fields {
+        let value = values.get(&field.name).map(String::as_str).unwrap_or("");
+
+        if field.required && value.is_empty() {
+            return Err(format!("missing required field '{}'", field.name));
+        }
+        if value.is_empty() {
+            continue;
+        }
+
+        if let CredentialFieldType::Enum { allowed } = &field.field_type {
+            if !allowed.iter().any(|a| a == value) {
+                return Err(format!("field '{}' must be one of {:?}", field.name, allowed));
+            }
+        }
+
+        if let Some(pattern) = &field.validation_regex {
+            let re = Regex::new(pattern)
+                .map_err(|e| format!("invalid validation regex for field '{}': {e}", field.name))?;
+            if !re.is_match(value) {
+                return Err(format!("field '{}' does not match the required format", field.name));
+            }
+        }
     }
+
+    Ok(())
 }
@@ -1,158 +1,452 @@
-use rusqlite::{Connection, Result, params};
+use std::collections::HashSet;
 use std::path::Path;
+use std::sync::Arc;
+
 use chrono::{DateTime, Utc};
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::params;
 use serde_json::Value;
+use thiserror::Error;
 use uuid::Uuid;
 
-use crate::{AgentConfig, tasks, scheduler, triggers};
+use crate::{AgentConfig, backoff, tasks, scheduler, triggers};
+use crate::backoff::DeadLetterSink;
+use crate::errors::TaskError;
+use crate::events::EventBus;
+use crate::notifications::{ApprovalEvent, Notifier};
+use crate::policy::{Policy, PolicyOutcome};
+use crate::tasks::TaskExecutionConfig;
+
+#[derive(Debug, Error)]
+pub enum DatabaseError {
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("failed to check out a pooled connection: {0}")]
+    Pool(#[from] r2d2::Error),
+    /// `process_approval`'s compare-and-set matched zero rows: the request
+    /// had already moved off `expected_status` by the time we updated it,
+    /// either decided by another reviewer or re-submitted.
+    #[error("approval request {0} was already decided or modified concurrently")]
+    ApprovalConflict(String),
+}
+
+pub type Result<T> = std::result::Result<T, DatabaseError>;
+
+/// Schema migrations, oldest first. Index `i` brings the database from
+/// `user_version == i` to `user_version == i + 1`; `run_migrations` applies
+/// every entry past the database's current version. A migration, once
+/// released, must never be edited — add a new one instead, or a database
+/// that already applied the old text diverges from one that hasn't.
+const MIGRATIONS: &[&str] = &[
+    // 1: initial schema
+    "CREATE TABLE IF NOT EXISTS agents (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        goal TEXT NOT NULL,
+        personality TEXT NOT NULL,
+        provider TEXT NOT NULL,
+        model TEXT NOT NULL,
+        temperature REAL NOT NULL,
+        tools TEXT NOT NULL,
+        autonomy_level INTEGER NOT NULL DEFAULT 2,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS tasks (
+        id TEXT PRIMARY KEY,
+        agent_id TEXT NOT NULL,
+        task_type TEXT NOT NULL,
+        input TEXT NOT NULL,
+        status TEXT NOT NULL DEFAULT 'pending',
+        result TEXT,
+        error TEXT,
+        scheduled_at TEXT,
+        started_at TEXT,
+        completed_at TEXT,
+        created_at TEXT NOT NULL,
+        retry_count INTEGER NOT NULL DEFAULT 0,
+        FOREIGN KEY (agent_id) REFERENCES agents(id)
+    );
+    CREATE TABLE IF NOT EXISTS schedules (
+        id TEXT PRIMARY KEY,
+        agent_id TEXT NOT NULL,
+        name TEXT NOT NULL,
+        cron_expr TEXT,
+        run_at TEXT,
+        task_type TEXT NOT NULL,
+        task_input TEXT NOT NULL,
+        enabled INTEGER NOT NULL DEFAULT 1,
+        last_run TEXT,
+        next_run TEXT,
+        created_at TEXT NOT NULL,
+        FOREIGN KEY (agent_id) REFERENCES agents(id)
+    );
+    CREATE TABLE IF NOT EXISTS triggers (
+        id TEXT PRIMARY KEY,
+        agent_id TEXT NOT NULL,
+        name TEXT NOT NULL,
+        trigger_type TEXT NOT NULL,
+        config TEXT NOT NULL,
+        task_type TEXT NOT NULL,
+        task_input TEXT NOT NULL,
+        enabled INTEGER NOT NULL DEFAULT 1,
+        last_triggered TEXT,
+        created_at TEXT NOT NULL,
+        FOREIGN KEY (agent_id) REFERENCES agents(id)
+    );
+    CREATE TABLE IF NOT EXISTS activity_log (
+        id TEXT PRIMARY KEY,
+        agent_id TEXT,
+        task_id TEXT,
+        action TEXT NOT NULL,
+        details TEXT,
+        timestamp TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS approval_requests (
+        id TEXT PRIMARY KEY,
+        agent_id TEXT NOT NULL,
+        task_id TEXT,
+        action_type TEXT NOT NULL,
+        action_details TEXT NOT NULL,
+        risk_level TEXT NOT NULL,
+        status TEXT NOT NULL DEFAULT 'pending',
+        decision TEXT,
+        modified_input TEXT,
+        created_at TEXT NOT NULL,
+        decided_at TEXT,
+        FOREIGN KEY (agent_id) REFERENCES agents(id)
+    );
+    CREATE INDEX IF NOT EXISTS idx_tasks_agent ON tasks(agent_id);
+    CREATE INDEX IF NOT EXISTS idx_tasks_status ON tasks(status);
+    CREATE INDEX IF NOT EXISTS idx_activity_timestamp ON activity_log(timestamp);",
+    // 2: per-task retry policy and exponential-backoff retry scheduling
+    "ALTER TABLE tasks ADD COLUMN max_retries INTEGER NOT NULL DEFAULT 3;
+    ALTER TABLE tasks ADD COLUMN next_retry_at TEXT;",
+    // 3: provenance for approval decisions, so the audit trail can tell an
+    // automated policy decision apart from a human reviewer's
+    "ALTER TABLE approval_requests ADD COLUMN decided_by TEXT;",
+    // 4: per-schedule timezone for matching local wall-clock cron times, and
+    // a missed-run catch-up policy flag
+    "ALTER TABLE schedules ADD COLUMN timezone TEXT;
+    ALTER TABLE schedules ADD COLUMN catch_up_missed INTEGER NOT NULL DEFAULT 1;",
+];
+
+/// Every column `list_approvals` can project, in `SELECT` order. `id` is
+/// always included by that method regardless of the caller's `fields`,
+/// since it's how a projected row is identified.
+const APPROVAL_COLUMNS: &[&str] = &[
+    "id", "agent_id", "task_id", "action_type", "action_details", "risk_level", "status", "created_at",
+    "decided_at", "decided_by",
+];
+
+/// The persistence surface the rest of the app talks to. Extracted as a
+/// trait (rather than calling into `Database` directly) so scheduler and
+/// trigger logic can be exercised against a canned in-memory double instead
+/// of a real SQLite file — under `#[cfg(test)]`, `mockall::automock`
+/// generates a `MockStore` that returns whatever a test tells it to.
+#[cfg_attr(test, mockall::automock)]
+pub trait Store {
+    fn save_agent(&self, config: &AgentConfig) -> Result<String>;
+    fn get_agents(&self) -> Result<Vec<AgentConfig>>;
+    fn get_agent(&self, agent_id: &str) -> Result<Option<AgentConfig>>;
+    fn delete_agent(&self, agent_id: &str) -> Result<()>;
+
+    fn create_task(
+        &self,
+        agent_id: &str,
+        task_type: &str,
+        input: Value,
+        scheduled_at: Option<String>,
+    ) -> Result<tasks::Task>;
+    fn get_tasks(&self, agent_id: Option<&str>, status: Option<&str>) -> Result<Vec<tasks::Task>>;
+    fn get_task(&self, task_id: &str) -> Result<Option<tasks::Task>>;
+    fn claim_next_task(&self, agent_id: Option<&str>) -> Result<Option<tasks::Task>>;
+    /// Put a just-claimed task back to `pending`, clearing the `started_at`
+    /// the claim set, for a caller (e.g. a throttle check) that decides not
+    /// to run the task after all. Distinct from `update_task_status` so the
+    /// revert can't leave a `pending` task with a stale start timestamp.
+    fn revert_claim(&self, task_id: &str) -> Result<()>;
+    fn update_task_status(
+        &self,
+        task_id: &str,
+        status: &str,
+        result: Option<Value>,
+        error: Option<String>,
+    ) -> Result<()>;
+    fn fail_task(&self, task_id: &str, error: TaskError, config: &TaskExecutionConfig) -> Result<()>;
+    fn set_retry_policy(&self, task_id: &str, max_retries: u32) -> Result<()>;
+
+    fn create_schedule(
+        &self,
+        agent_id: &str,
+        name: &str,
+        cron_expr: Option<&str>,
+        run_at: Option<&str>,
+        task_type: &str,
+        task_input: Value,
+        timezone: Option<&str>,
+        catch_up_missed: bool,
+    ) -> Result<scheduler::Schedule>;
+    fn get_schedules(&self, agent_id: Option<&str>) -> Result<Vec<scheduler::Schedule>>;
+    fn delete_schedule(&self, schedule_id: &str) -> Result<()>;
+    fn toggle_schedule(&self, schedule_id: &str, enabled: bool) -> Result<()>;
+    fn claim_due_schedules(&self, now: DateTime<Utc>) -> Result<Vec<scheduler::Schedule>>;
+    fn record_schedule_run(&self, schedule_id: &str, next_run: Option<&str>) -> Result<()>;
+
+    fn create_trigger(
+        &self,
+        agent_id: &str,
+        name: &str,
+        trigger_type: &str,
+        config: Value,
+        task_type: &str,
+        task_input: Value,
+    ) -> Result<triggers::Trigger>;
+    fn get_triggers(&self, agent_id: Option<&str>) -> Result<Vec<triggers::Trigger>>;
+    fn delete_trigger(&self, trigger_id: &str) -> Result<()>;
+
+    fn log_activity(
+        &self,
+        agent_id: Option<&str>,
+        task_id: Option<&str>,
+        action: &str,
+        details: Option<&str>,
+    ) -> Result<()>;
+    fn get_activity_log(&self, agent_id: Option<&str>, limit: u32) -> Result<Vec<tasks::ActivityLogEntry>>;
+    fn get_activity(&self, filter: tasks::ActivityFilter) -> Result<Vec<tasks::ActivityLogEntry>>;
+    fn get_task_stats(&self, agent_id: Option<&str>) -> Result<tasks::TaskStats>;
+    fn get_task_throughput(
+        &self,
+        agent_id: Option<&str>,
+        bucket: tasks::Granularity,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<(String, u32, u32, Option<f64>)>>;
+
+    fn create_approval_request(
+        &self,
+        agent_id: &str,
+        task_id: Option<&str>,
+        action_type: &str,
+        action_details: Value,
+        risk_level: &str,
+    ) -> Result<tasks::ApprovalRequest>;
+    fn get_pending_approvals(&self) -> Result<Vec<tasks::ApprovalRequest>>;
+    fn list_approvals_by_status(&self, status: Option<&str>) -> Result<Vec<tasks::ApprovalRequest>>;
+    fn list_approvals(&self, query: tasks::ListApprovalsQuery) -> Result<Vec<tasks::ApprovalRequest>>;
+    fn process_approval(
+        &self,
+        approval_id: &str,
+        approved: bool,
+        modified_input: Option<Value>,
+        expected_status: &str,
+    ) -> Result<()>;
+
+    fn current_schema_version(&self) -> Result<u32>;
+}
 
 pub struct Database {
-    conn: Connection,
+    pool: Pool<SqliteConnectionManager>,
+    events: Arc<EventBus>,
+    /// Fires approval lifecycle events (requested/decided) out-of-band so
+    /// operators don't have to poll `approval_requests` to notice a new or
+    /// resolved request. `None` when no channels are configured.
+    notifier: Option<Arc<Notifier>>,
+    /// Evaluated against every new action in `create_approval_request`
+    /// before it reaches the table, so routine low-risk actions can be
+    /// auto-approved/auto-rejected instead of always blocking on a human.
+    /// `None` means every request requires a human, as before this existed.
+    policy: Option<Policy>,
+    /// Receives a `DeadLetterReport` from `fail_task` whenever a task
+    /// exhausts its retries. `None` means dead-lettered tasks are simply
+    /// left in the `failed` status with no further notification.
+    dead_letter_sink: Option<Arc<dyn DeadLetterSink>>,
 }
 
 impl Database {
-    pub fn new(path: &Path) -> Result<Self> {
-        let conn = Connection::open(path)?;
-        let db = Database { conn };
-        db.initialize()?;
+    pub fn new(
+        path: &Path,
+        events: Arc<EventBus>,
+        notifier: Option<Arc<Notifier>>,
+        policy: Option<Policy>,
+        dead_letter_sink: Option<Arc<dyn DeadLetterSink>>,
+    ) -> Result<Self> {
+        // WAL lets readers and the writer proceed concurrently instead of
+        // blocking each other; `busy_timeout` makes a connection that does
+        // have to wait for the write lock retry instead of failing
+        // immediately with `SQLITE_BUSY`.
+        let manager = SqliteConnectionManager::file(path).with_init(|conn| {
+            conn.execute_batch(
+                "PRAGMA journal_mode = WAL;
+                 PRAGMA synchronous = NORMAL;
+                 PRAGMA busy_timeout = 5000;",
+            )
+        });
+        let pool = Pool::new(manager)?;
+        let db = Database { pool, events, notifier, policy, dead_letter_sink };
+        db.run_migrations()?;
         Ok(db)
     }
 
-    fn initialize(&self) -> Result<()> {
-        // Agents table
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS agents (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                goal TEXT NOT NULL,
-                personality TEXT NOT NULL,
-                provider TEXT NOT NULL,
-                model TEXT NOT NULL,
-                temperature REAL NOT NULL,
-                tools TEXT NOT NULL,
-                autonomy_level INTEGER NOT NULL DEFAULT 2,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            )",
-            [],
-        )?;
+    fn conn(&self) -> Result<r2d2::PooledConnection<SqliteConnectionManager>> {
+        Ok(self.pool.get()?)
+    }
 
-        // Tasks table
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS tasks (
-                id TEXT PRIMARY KEY,
-                agent_id TEXT NOT NULL,
-                task_type TEXT NOT NULL,
-                input TEXT NOT NULL,
-                status TEXT NOT NULL DEFAULT 'pending',
-                result TEXT,
-                error TEXT,
-                scheduled_at TEXT,
-                started_at TEXT,
-                completed_at TEXT,
-                created_at TEXT NOT NULL,
-                retry_count INTEGER NOT NULL DEFAULT 0,
-                FOREIGN KEY (agent_id) REFERENCES agents(id)
-            )",
-            [],
-        )?;
+    /// Apply every migration after the database's current `user_version`,
+    /// in order, inside one transaction — either the whole batch lands or
+    /// none of it does, so a crash mid-migration can't leave the schema
+    /// half-upgraded. `IF NOT EXISTS`/`IF NOT EXISTS` guards stay on the
+    /// individual statements as a defensive belt-and-suspenders for
+    /// databases that predate this migration list.
+    fn run_migrations(&self) -> Result<()> {
+        let conn = self.conn()?;
+        let current: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if current as usize >= MIGRATIONS.len() {
+            return Ok(());
+        }
 
-        // Schedules table
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS schedules (
-                id TEXT PRIMARY KEY,
-                agent_id TEXT NOT NULL,
-                name TEXT NOT NULL,
-                cron_expr TEXT,
-                run_at TEXT,
-                task_type TEXT NOT NULL,
-                task_input TEXT NOT NULL,
-                enabled INTEGER NOT NULL DEFAULT 1,
-                last_run TEXT,
-                next_run TEXT,
-                created_at TEXT NOT NULL,
-                FOREIGN KEY (agent_id) REFERENCES agents(id)
-            )",
-            [],
-        )?;
+        conn.execute_batch("BEGIN IMMEDIATE")?;
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+            if let Err(err) = conn.execute_batch(migration) {
+                conn.execute_batch("ROLLBACK")?;
+                return Err(err.into());
+            }
+            // `user_version` doesn't accept bound parameters, but `index`
+            // is ours, not user input, so formatting it in is safe.
+            if let Err(err) = conn.execute_batch(&format!("PRAGMA user_version = {}", index + 1)) {
+                conn.execute_batch("ROLLBACK")?;
+                return Err(err.into());
+            }
+        }
+        conn.execute_batch("COMMIT")?;
 
-        // Triggers table
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS triggers (
-                id TEXT PRIMARY KEY,
-                agent_id TEXT NOT NULL,
-                name TEXT NOT NULL,
-                trigger_type TEXT NOT NULL,
-                config TEXT NOT NULL,
-                task_type TEXT NOT NULL,
-                task_input TEXT NOT NULL,
-                enabled INTEGER NOT NULL DEFAULT 1,
-                last_triggered TEXT,
-                created_at TEXT NOT NULL,
-                FOREIGN KEY (agent_id) REFERENCES agents(id)
-            )",
-            [],
-        )?;
+        Ok(())
+    }
 
-        // Activity log table
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS activity_log (
-                id TEXT PRIMARY KEY,
-                agent_id TEXT,
-                task_id TEXT,
-                action TEXT NOT NULL,
-                details TEXT,
-                timestamp TEXT NOT NULL
-            )",
-            [],
-        )?;
+    fn row_to_task(row: &rusqlite::Row) -> rusqlite::Result<tasks::Task> {
+        let input_json: String = row.get(3)?;
+        let result_json: Option<String> = row.get(5)?;
 
-        // Approval requests table
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS approval_requests (
-                id TEXT PRIMARY KEY,
-                agent_id TEXT NOT NULL,
-                task_id TEXT,
-                action_type TEXT NOT NULL,
-                action_details TEXT NOT NULL,
-                risk_level TEXT NOT NULL,
-                status TEXT NOT NULL DEFAULT 'pending',
-                decision TEXT,
-                modified_input TEXT,
-                created_at TEXT NOT NULL,
-                decided_at TEXT,
-                FOREIGN KEY (agent_id) REFERENCES agents(id)
-            )",
-            [],
-        )?;
+        Ok(tasks::Task {
+            id: row.get(0)?,
+            agent_id: row.get(1)?,
+            task_type: row.get(2)?,
+            input: serde_json::from_str(&input_json).unwrap_or(Value::Null),
+            status: row.get(4)?,
+            result: result_json.and_then(|s| serde_json::from_str(&s).ok()),
+            error: row.get(6)?,
+            scheduled_at: row.get(7)?,
+            started_at: row.get(8)?,
+            completed_at: row.get(9)?,
+            created_at: row.get(10)?,
+            retry_count: row.get(11)?,
+            max_retries: row.get(12)?,
+            next_retry_at: row.get(13)?,
+            attempt_timestamps: Vec::new(),
+        })
+    }
 
-        // Create indexes
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_tasks_agent ON tasks(agent_id)",
-            [],
-        )?;
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_tasks_status ON tasks(status)",
-            [],
-        )?;
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_activity_timestamp ON activity_log(timestamp)",
-            [],
-        )?;
+    fn row_to_schedule(row: &rusqlite::Row) -> rusqlite::Result<scheduler::Schedule> {
+        let input_json: String = row.get(6)?;
 
-        Ok(())
+        Ok(scheduler::Schedule {
+            id: row.get(0)?,
+            agent_id: row.get(1)?,
+            name: row.get(2)?,
+            cron_expr: row.get(3)?,
+            run_at: row.get(4)?,
+            task_type: row.get(5)?,
+            task_input: serde_json::from_str(&input_json).unwrap_or(Value::Null),
+            enabled: row.get::<_, i32>(7)? != 0,
+            last_run: row.get(8)?,
+            next_run: row.get(9)?,
+            created_at: row.get(10)?,
+            timezone: row.get(11)?,
+            catch_up_missed: row.get::<_, i32>(12)? != 0,
+        })
+    }
+
+    fn row_to_trigger(row: &rusqlite::Row) -> rusqlite::Result<triggers::Trigger> {
+        let config_json: String = row.get(4)?;
+        let input_json: String = row.get(6)?;
+
+        Ok(triggers::Trigger {
+            id: row.get(0)?,
+            agent_id: row.get(1)?,
+            name: row.get(2)?,
+            trigger_type: row.get(3)?,
+            config: serde_json::from_str(&config_json).unwrap_or(Value::Null),
+            task_type: row.get(5)?,
+            task_input: serde_json::from_str(&input_json).unwrap_or(Value::Null),
+            enabled: row.get::<_, i32>(7)? != 0,
+            last_triggered: row.get(8)?,
+            created_at: row.get(9)?,
+        })
+    }
+
+    fn row_to_throughput_bucket(row: &rusqlite::Row) -> rusqlite::Result<(String, u32, u32, Option<f64>)> {
+        Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+    }
+
+    fn row_to_approval(row: &rusqlite::Row) -> rusqlite::Result<tasks::ApprovalRequest> {
+        let details_json: String = row.get(4)?;
+        Ok(tasks::ApprovalRequest {
+            id: row.get(0)?,
+            agent_id: row.get(1)?,
+            task_id: row.get(2)?,
+            action_type: row.get(3)?,
+            action_details: serde_json::from_str(&details_json).unwrap_or(Value::Null),
+            risk_level: row.get(5)?,
+            status: row.get(6)?,
+            created_at: row.get(7)?,
+            decided_at: row.get(8)?,
+            decided_by: row.get(9)?,
+        })
+    }
+
+    /// Like `row_to_approval`, but for a `SELECT` projected down to
+    /// `columns` (from `list_approvals`'s field filter): any
+    /// `ApprovalRequest` field whose column wasn't selected falls back to
+    /// its type's default instead of being read from the row.
+    fn row_to_projected_approval(
+        row: &rusqlite::Row,
+        columns: &HashSet<&str>,
+    ) -> rusqlite::Result<tasks::ApprovalRequest> {
+        let action_details = if columns.contains("action_details") {
+            let details_json: String = row.get("action_details")?;
+            serde_json::from_str(&details_json).unwrap_or(Value::Null)
+        } else {
+            Value::Null
+        };
+
+        Ok(tasks::ApprovalRequest {
+            id: row.get("id")?,
+            agent_id: if columns.contains("agent_id") { row.get("agent_id")? } else { String::new() },
+            task_id: if columns.contains("task_id") { row.get("task_id")? } else { None },
+            action_type: if columns.contains("action_type") { row.get("action_type")? } else { String::new() },
+            action_details,
+            risk_level: if columns.contains("risk_level") { row.get("risk_level")? } else { String::new() },
+            status: if columns.contains("status") { row.get("status")? } else { String::new() },
+            created_at: if columns.contains("created_at") { row.get("created_at")? } else { String::new() },
+            decided_at: if columns.contains("decided_at") { row.get("decided_at")? } else { None },
+            decided_by: if columns.contains("decided_by") { row.get("decided_by")? } else { None },
+        })
     }
+}
 
+impl Store for Database {
     // ========================================================================
     // Agent Operations
     // ========================================================================
 
-    pub fn save_agent(&self, config: &AgentConfig) -> Result<String> {
+    fn save_agent(&self, config: &AgentConfig) -> Result<String> {
         let id = config.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
         let now = Utc::now().to_rfc3339();
         let tools_json = serde_json::to_string(&config.tools).unwrap_or_else(|_| "[]".to_string());
 
-        self.conn.execute(
+        let conn = self.conn()?;
+        conn.execute(
             "INSERT INTO agents (id, name, goal, personality, provider, model, temperature, tools, autonomy_level, created_at, updated_at)
              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?10)
              ON CONFLICT(id) DO UPDATE SET
@@ -161,20 +455,22 @@ impl Database {
             params![id, config.name, config.goal, config.personality, config.provider,
                     config.model, config.temperature, tools_json, config.autonomy_level, now],
         )?;
+        drop(conn);
 
         self.log_activity(Some(&id), None, "agent_saved", Some(&format!("Agent '{}' saved", config.name)))?;
         Ok(id)
     }
 
-    pub fn get_agents(&self) -> Result<Vec<AgentConfig>> {
-        let mut stmt = self.conn.prepare(
+    fn get_agents(&self) -> Result<Vec<AgentConfig>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
             "SELECT id, name, goal, personality, provider, model, temperature, tools, autonomy_level FROM agents"
         )?;
-        
+
         let agents = stmt.query_map([], |row| {
             let tools_json: String = row.get(7)?;
             let tools: Vec<String> = serde_json::from_str(&tools_json).unwrap_or_default();
-            
+
             Ok(AgentConfig {
                 id: Some(row.get(0)?),
                 name: row.get(1)?,
@@ -186,23 +482,24 @@ impl Database {
                 tools,
                 autonomy_level: row.get(8)?,
             })
-        })?.collect::<Result<Vec<_>>>()?;
-        
+        })?.collect::<rusqlite::Result<Vec<_>>>()?;
+
         Ok(agents)
     }
 
-    pub fn get_agent(&self, agent_id: &str) -> Result<Option<AgentConfig>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, name, goal, personality, provider, model, temperature, tools, autonomy_level 
+    fn get_agent(&self, agent_id: &str) -> Result<Option<AgentConfig>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, name, goal, personality, provider, model, temperature, tools, autonomy_level
              FROM agents WHERE id = ?1"
         )?;
-        
+
         let mut rows = stmt.query(params![agent_id])?;
-        
+
         if let Some(row) = rows.next()? {
             let tools_json: String = row.get(7)?;
             let tools: Vec<String> = serde_json::from_str(&tools_json).unwrap_or_default();
-            
+
             Ok(Some(AgentConfig {
                 id: Some(row.get(0)?),
                 name: row.get(1)?,
@@ -219,8 +516,8 @@ impl Database {
         }
     }
 
-    pub fn delete_agent(&self, agent_id: &str) -> Result<()> {
-        self.conn.execute("DELETE FROM agents WHERE id = ?1", params![agent_id])?;
+    fn delete_agent(&self, agent_id: &str) -> Result<()> {
+        self.conn()?.execute("DELETE FROM agents WHERE id = ?1", params![agent_id])?;
         self.log_activity(Some(agent_id), None, "agent_deleted", None)?;
         Ok(())
     }
@@ -229,7 +526,7 @@ impl Database {
     // Task Operations
     // ========================================================================
 
-    pub fn create_task(
+    fn create_task(
         &self,
         agent_id: &str,
         task_type: &str,
@@ -241,7 +538,7 @@ impl Database {
         let input_json = serde_json::to_string(&input).unwrap_or_else(|_| "{}".to_string());
         let status = if scheduled_at.is_some() { "scheduled" } else { "pending" };
 
-        self.conn.execute(
+        self.conn()?.execute(
             "INSERT INTO tasks (id, agent_id, task_type, input, status, scheduled_at, created_at)
              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             params![id, agent_id, task_type, input_json, status, scheduled_at, now],
@@ -249,6 +546,10 @@ impl Database {
 
         self.log_activity(Some(agent_id), Some(&id), "task_created", Some(&format!("Task '{}' created", task_type)))?;
 
+        if let Some(parsed_status) = tasks::TaskStatus::parse(status) {
+            self.events.publish_task_status(&id, agent_id, task_type, parsed_status);
+        }
+
         Ok(tasks::Task {
             id: id.clone(),
             agent_id: agent_id.to_string(),
@@ -262,14 +563,18 @@ impl Database {
             completed_at: None,
             created_at: now,
             retry_count: 0,
+            max_retries: tasks::default_max_retries(),
+            next_retry_at: None,
+            attempt_timestamps: Vec::new(),
         })
     }
 
-    pub fn get_tasks(&self, agent_id: Option<&str>, status: Option<&str>) -> Result<Vec<tasks::Task>> {
-        let mut sql = "SELECT id, agent_id, task_type, input, status, result, error, 
-                       scheduled_at, started_at, completed_at, created_at, retry_count 
+    fn get_tasks(&self, agent_id: Option<&str>, status: Option<&str>) -> Result<Vec<tasks::Task>> {
+        let mut sql = "SELECT id, agent_id, task_type, input, status, result, error,
+                       scheduled_at, started_at, completed_at, created_at, retry_count,
+                       max_retries, next_retry_at
                        FROM tasks WHERE 1=1".to_string();
-        
+
         if agent_id.is_some() {
             sql.push_str(" AND agent_id = ?1");
         }
@@ -278,55 +583,136 @@ impl Database {
         }
         sql.push_str(" ORDER BY created_at DESC LIMIT 100");
 
-        let mut stmt = self.conn.prepare(&sql)?;
-        
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&sql)?;
+
         let tasks = match (agent_id, status) {
-            (Some(aid), Some(st)) => stmt.query_map(params![aid, st], Self::row_to_task)?,
-            (Some(aid), None) => stmt.query_map(params![aid], Self::row_to_task)?,
-            (None, Some(st)) => stmt.query_map(params![st], Self::row_to_task)?,
-            (None, None) => stmt.query_map([], Self::row_to_task)?,
-        }.collect::<Result<Vec<_>>>()?;
-        
+            (Some(aid), Some(st)) => stmt.query_map(params![aid, st], Database::row_to_task)?,
+            (Some(aid), None) => stmt.query_map(params![aid], Database::row_to_task)?,
+            (None, Some(st)) => stmt.query_map(params![st], Database::row_to_task)?,
+            (None, None) => stmt.query_map([], Database::row_to_task)?,
+        }.collect::<rusqlite::Result<Vec<_>>>()?;
+
         Ok(tasks)
     }
 
-    pub fn get_task(&self, task_id: &str) -> Result<Option<tasks::Task>> {
-        let mut stmt = self.conn.prepare(
+    fn get_task(&self, task_id: &str) -> Result<Option<tasks::Task>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
             "SELECT id, agent_id, task_type, input, status, result, error,
-                    scheduled_at, started_at, completed_at, created_at, retry_count
+                    scheduled_at, started_at, completed_at, created_at, retry_count,
+                    max_retries, next_retry_at
              FROM tasks WHERE id = ?1"
         )?;
-        
+
         let mut rows = stmt.query(params![task_id])?;
-        
+
         if let Some(row) = rows.next()? {
-            Ok(Some(Self::row_to_task(row)?))
+            Ok(Some(Database::row_to_task(row)?))
         } else {
             Ok(None)
         }
     }
 
-    fn row_to_task(row: &rusqlite::Row) -> Result<tasks::Task> {
-        let input_json: String = row.get(3)?;
-        let result_json: Option<String> = row.get(5)?;
-        
-        Ok(tasks::Task {
-            id: row.get(0)?,
-            agent_id: row.get(1)?,
-            task_type: row.get(2)?,
-            input: serde_json::from_str(&input_json).unwrap_or(Value::Null),
-            status: row.get(4)?,
-            result: result_json.and_then(|s| serde_json::from_str(&s).ok()),
-            error: row.get(6)?,
-            scheduled_at: row.get(7)?,
-            started_at: row.get(8)?,
-            completed_at: row.get(9)?,
-            created_at: row.get(10)?,
-            retry_count: row.get(11)?,
-        })
+    /// Atomically claim the oldest pending (or due-scheduled) task and mark
+    /// it `running`, so two workers racing `claim_next_task` can never pick
+    /// up the same row. Uses `BEGIN IMMEDIATE` on a single pooled connection
+    /// to take SQLite's write lock up front instead of upgrading a read
+    /// lock later, which is what deadlocks when two connections try it at
+    /// once. If our `UPDATE` loses the race to a concurrent claimant (it
+    /// affects zero rows because the status already moved), we roll back
+    /// and retry the select rather than returning a stale task.
+    fn claim_next_task(&self, agent_id: Option<&str>) -> Result<Option<tasks::Task>> {
+        let conn = self.conn()?;
+        loop {
+            conn.execute_batch("BEGIN IMMEDIATE")?;
+
+            // A `scheduled` task is due either at its original one-time
+            // `scheduled_at`, or at `next_retry_at` if a prior failure
+            // rescheduled it for a backoff retry — whichever is set.
+            let sql = if agent_id.is_some() {
+                "SELECT id, agent_id, task_type, input, status, result, error,
+                        scheduled_at, started_at, completed_at, created_at, retry_count,
+                        max_retries, next_retry_at
+                 FROM tasks
+                 WHERE agent_id = ?1 AND (status = 'pending' OR (status = 'scheduled' AND COALESCE(next_retry_at, scheduled_at) <= ?2))
+                 ORDER BY created_at ASC LIMIT 1"
+            } else {
+                "SELECT id, agent_id, task_type, input, status, result, error,
+                        scheduled_at, started_at, completed_at, created_at, retry_count,
+                        max_retries, next_retry_at
+                 FROM tasks
+                 WHERE status = 'pending' OR (status = 'scheduled' AND COALESCE(next_retry_at, scheduled_at) <= ?1)
+                 ORDER BY created_at ASC LIMIT 1"
+            };
+            let now = Utc::now().to_rfc3339();
+
+            let claimed: Option<tasks::Task> = {
+                let mut stmt = conn.prepare(sql)?;
+                let mut rows = if let Some(aid) = agent_id {
+                    stmt.query(params![aid, now])?
+                } else {
+                    stmt.query(params![now])?
+                };
+                match rows.next()? {
+                    Some(row) => Some(Database::row_to_task(row)?),
+                    None => None,
+                }
+            };
+
+            let Some(task) = claimed else {
+                conn.execute_batch("ROLLBACK")?;
+                return Ok(None);
+            };
+
+            let claimed_rows = conn.execute(
+                "UPDATE tasks SET status = 'running', started_at = ?1 WHERE id = ?2 AND status IN ('pending', 'scheduled')",
+                params![now, task.id],
+            )?;
+
+            if claimed_rows != 1 {
+                // Another worker claimed this row between our SELECT and
+                // UPDATE; retry rather than hand back a stale task.
+                conn.execute_batch("ROLLBACK")?;
+                continue;
+            }
+
+            conn.execute_batch("COMMIT")?;
+            drop(conn);
+
+            self.log_activity(Some(&task.agent_id), Some(&task.id), "task_claimed", None)?;
+            self.events
+                .publish_task_status(&task.id, &task.agent_id, &task.task_type, tasks::TaskStatus::Running);
+
+            return Ok(Some(tasks::Task {
+                status: "running".to_string(),
+                started_at: Some(now),
+                ..task
+            }));
+        }
     }
 
-    pub fn update_task_status(
+    fn revert_claim(&self, task_id: &str) -> Result<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            "UPDATE tasks SET status = 'pending', started_at = NULL WHERE id = ?1",
+            params![task_id],
+        )?;
+        let identity: Option<(String, String)> = conn.query_row(
+            "SELECT agent_id, task_type FROM tasks WHERE id = ?1",
+            params![task_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).ok();
+        drop(conn);
+
+        self.log_activity(identity.as_ref().map(|(agent_id, _)| agent_id.as_str()), Some(task_id), "task_pending", None)?;
+        if let Some((agent_id, task_type)) = identity {
+            self.events.publish_task_status(task_id, &agent_id, &task_type, tasks::TaskStatus::Pending);
+        }
+        Ok(())
+    }
+
+    fn update_task_status(
         &self,
         task_id: &str,
         status: &str,
@@ -335,38 +721,115 @@ impl Database {
     ) -> Result<()> {
         let now = Utc::now().to_rfc3339();
         let result_json = result.map(|r| serde_json::to_string(&r).unwrap_or_default());
-        
+
         let (started_at, completed_at) = match status {
             "running" => (Some(now.clone()), None),
             "completed" | "failed" | "cancelled" => (None, Some(now.clone())),
             _ => (None, None),
         };
 
+        let conn = self.conn()?;
         if let Some(started) = started_at {
-            self.conn.execute(
+            conn.execute(
                 "UPDATE tasks SET status = ?1, started_at = ?2 WHERE id = ?3",
                 params![status, started, task_id],
             )?;
         } else if let Some(completed) = completed_at {
-            self.conn.execute(
+            conn.execute(
                 "UPDATE tasks SET status = ?1, result = ?2, error = ?3, completed_at = ?4 WHERE id = ?5",
                 params![status, result_json, error, completed, task_id],
             )?;
         } else {
-            self.conn.execute(
+            conn.execute(
                 "UPDATE tasks SET status = ?1 WHERE id = ?2",
                 params![status, task_id],
             )?;
         }
 
-        // Get agent_id for logging
-        let agent_id: Option<String> = self.conn.query_row(
-            "SELECT agent_id FROM tasks WHERE id = ?1",
+        // Get agent_id/task_type for logging and for the event-bus publish below
+        let identity: Option<(String, String)> = conn.query_row(
+            "SELECT agent_id, task_type FROM tasks WHERE id = ?1",
             params![task_id],
-            |row| row.get(0),
+            |row| Ok((row.get(0)?, row.get(1)?)),
         ).ok();
+        drop(conn);
+
+        self.log_activity(
+            identity.as_ref().map(|(agent_id, _)| agent_id.as_str()),
+            Some(task_id),
+            &format!("task_{}", status),
+            None,
+        )?;
+
+        if let (Some((agent_id, task_type)), Some(parsed_status)) =
+            (identity, tasks::TaskStatus::parse(status))
+        {
+            self.events.publish_task_status(task_id, &agent_id, &task_type, parsed_status);
+        }
 
-        self.log_activity(agent_id.as_deref(), Some(task_id), &format!("task_{}", status), None)?;
+        Ok(())
+    }
+
+    /// Record a failed attempt and either reschedule it for a jittered
+    /// exponential-backoff retry or dead-letter it, per
+    /// `backoff::evaluate_failure` — which gates the decision on
+    /// `error.is_retryable()` as well as `max_retries`, so an
+    /// `ApprovalDenied`/`ThrottleRejected`/`Irrecoverable` failure
+    /// dead-letters immediately instead of burning a retry attempt. Unlike
+    /// `update_task_status("failed", ...)`, this is the entry point the
+    /// task runner should call on failure so retries actually happen.
+    fn fail_task(&self, task_id: &str, error: TaskError, config: &TaskExecutionConfig) -> Result<()> {
+        let Some(task) = self.get_task(task_id)? else {
+            return Ok(());
+        };
+
+        let legacy_error = error.to_legacy_string();
+        // The task's own `max_retries` column (see `set_retry_policy`)
+        // takes the place of `config.max_retries`.
+        let task_config = TaskExecutionConfig { max_retries: task.max_retries, ..config.clone() };
+        let outcome = backoff::evaluate_failure(&task, &task_config, &error);
+
+        let conn = self.conn()?;
+        let status = match &outcome {
+            backoff::RetryOutcome::Retry { scheduled_at } => {
+                conn.execute(
+                    "UPDATE tasks SET status = 'scheduled', retry_count = retry_count + 1, error = ?1, next_retry_at = ?2 WHERE id = ?3",
+                    params![legacy_error, scheduled_at, task_id],
+                )?;
+                "scheduled"
+            }
+            backoff::RetryOutcome::DeadLetter(_) => {
+                let now = Utc::now().to_rfc3339();
+                conn.execute(
+                    "UPDATE tasks SET status = 'failed', error = ?1, completed_at = ?2 WHERE id = ?3",
+                    params![legacy_error, now, task_id],
+                )?;
+                "failed"
+            }
+        };
+        drop(conn);
+
+        if let backoff::RetryOutcome::DeadLetter(report) = outcome {
+            if let Some(sink) = &self.dead_letter_sink {
+                sink.handle(report);
+            }
+        }
+
+        self.log_activity(Some(&task.agent_id), Some(task_id), &format!("task_{}", status), None)?;
+
+        if let Some(parsed_status) = tasks::TaskStatus::parse(status) {
+            self.events.publish_task_status(task_id, &task.agent_id, &task.task_type, parsed_status);
+        }
+
+        Ok(())
+    }
+
+    /// Set how many times `task_id` may be retried before it's dead-lettered.
+    fn set_retry_policy(&self, task_id: &str, max_retries: u32) -> Result<()> {
+        self.conn()?.execute(
+            "UPDATE tasks SET max_retries = ?1 WHERE id = ?2",
+            params![max_retries, task_id],
+        )?;
         Ok(())
     }
 
@@ -374,7 +837,7 @@ impl Database {
     // Schedule Operations
     // ========================================================================
 
-    pub fn create_schedule(
+    fn create_schedule(
         &self,
         agent_id: &str,
         name: &str,
@@ -382,15 +845,17 @@ impl Database {
         run_at: Option<&str>,
         task_type: &str,
         task_input: Value,
+        timezone: Option<&str>,
+        catch_up_missed: bool,
     ) -> Result<scheduler::Schedule> {
         let id = Uuid::new_v4().to_string();
         let now = Utc::now().to_rfc3339();
         let input_json = serde_json::to_string(&task_input).unwrap_or_else(|_| "{}".to_string());
 
-        self.conn.execute(
-            "INSERT INTO schedules (id, agent_id, name, cron_expr, run_at, task_type, task_input, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            params![id, agent_id, name, cron_expr, run_at, task_type, input_json, now],
+        self.conn()?.execute(
+            "INSERT INTO schedules (id, agent_id, name, cron_expr, run_at, task_type, task_input, created_at, timezone, catch_up_missed)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![id, agent_id, name, cron_expr, run_at, task_type, input_json, now, timezone, catch_up_missed],
         )?;
 
         self.log_activity(Some(agent_id), None, "schedule_created", Some(&format!("Schedule '{}' created", name)))?;
@@ -407,65 +872,112 @@ impl Database {
             last_run: None,
             next_run: None,
             created_at: now,
+            timezone: timezone.map(|s| s.to_string()),
+            catch_up_missed,
         })
     }
 
-    pub fn get_schedules(&self, agent_id: Option<&str>) -> Result<Vec<scheduler::Schedule>> {
+    fn get_schedules(&self, agent_id: Option<&str>) -> Result<Vec<scheduler::Schedule>> {
         let sql = if agent_id.is_some() {
-            "SELECT id, agent_id, name, cron_expr, run_at, task_type, task_input, enabled, last_run, next_run, created_at
+            "SELECT id, agent_id, name, cron_expr, run_at, task_type, task_input, enabled, last_run, next_run, created_at, timezone, catch_up_missed
              FROM schedules WHERE agent_id = ?1 ORDER BY created_at DESC"
         } else {
-            "SELECT id, agent_id, name, cron_expr, run_at, task_type, task_input, enabled, last_run, next_run, created_at
+            "SELECT id, agent_id, name, cron_expr, run_at, task_type, task_input, enabled, last_run, next_run, created_at, timezone, catch_up_missed
              FROM schedules ORDER BY created_at DESC"
         };
 
-        let mut stmt = self.conn.prepare(sql)?;
-        
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(sql)?;
+
         let schedules = if let Some(aid) = agent_id {
-            stmt.query_map(params![aid], Self::row_to_schedule)?
+            stmt.query_map(params![aid], Database::row_to_schedule)?
         } else {
-            stmt.query_map([], Self::row_to_schedule)?
-        }.collect::<Result<Vec<_>>>()?;
-        
-        Ok(schedules)
-    }
+            stmt.query_map([], Database::row_to_schedule)?
+        }.collect::<rusqlite::Result<Vec<_>>>()?;
 
-    fn row_to_schedule(row: &rusqlite::Row) -> Result<scheduler::Schedule> {
-        let input_json: String = row.get(6)?;
-        
-        Ok(scheduler::Schedule {
-            id: row.get(0)?,
-            agent_id: row.get(1)?,
-            name: row.get(2)?,
-            cron_expr: row.get(3)?,
-            run_at: row.get(4)?,
-            task_type: row.get(5)?,
-            task_input: serde_json::from_str(&input_json).unwrap_or(Value::Null),
-            enabled: row.get::<_, i32>(7)? != 0,
-            last_run: row.get(8)?,
-            next_run: row.get(9)?,
-            created_at: row.get(10)?,
-        })
+        Ok(schedules)
     }
 
-    pub fn delete_schedule(&self, schedule_id: &str) -> Result<()> {
-        self.conn.execute("DELETE FROM schedules WHERE id = ?1", params![schedule_id])?;
+    fn delete_schedule(&self, schedule_id: &str) -> Result<()> {
+        self.conn()?.execute("DELETE FROM schedules WHERE id = ?1", params![schedule_id])?;
         Ok(())
     }
 
-    pub fn toggle_schedule(&self, schedule_id: &str, enabled: bool) -> Result<()> {
-        self.conn.execute(
+    fn toggle_schedule(&self, schedule_id: &str, enabled: bool) -> Result<()> {
+        self.conn()?.execute(
             "UPDATE schedules SET enabled = ?1 WHERE id = ?2",
             params![enabled as i32, schedule_id],
         )?;
         Ok(())
     }
 
+    /// Atomically claim every enabled schedule due at or before `now` and
+    /// reschedule it in the same transaction, so two pollers racing this
+    /// call can't both dispatch the same occurrence. Recurring schedules
+    /// (`cron_expr` set) are pushed to their next cron occurrence via
+    /// `scheduler::compute_next_run_tz`; one-shot schedules (`run_at` only)
+    /// fire once and are disabled.
+    fn claim_due_schedules(&self, now: DateTime<Utc>) -> Result<Vec<scheduler::Schedule>> {
+        let conn = self.conn()?;
+        conn.execute_batch("BEGIN IMMEDIATE")?;
+        let now_str = now.to_rfc3339();
+
+        let due: rusqlite::Result<Vec<scheduler::Schedule>> = (|| {
+            let mut stmt = conn.prepare(
+                "SELECT id, agent_id, name, cron_expr, run_at, task_type, task_input, enabled, last_run, next_run, created_at, timezone, catch_up_missed
+                 FROM schedules
+                 WHERE enabled = 1 AND COALESCE(next_run, run_at, created_at) <= ?1
+                 ORDER BY COALESCE(next_run, run_at, created_at) ASC"
+            )?;
+            stmt.query_map(params![now_str], Database::row_to_schedule)?.collect()
+        })();
+
+        let due = match due {
+            Ok(rows) => rows,
+            Err(err) => {
+                conn.execute_batch("ROLLBACK")?;
+                return Err(err.into());
+            }
+        };
+
+        for schedule in &due {
+            if let Some(cron_expr) = &schedule.cron_expr {
+                let next_run = scheduler::compute_next_run_tz(cron_expr, now, schedule.timezone.as_deref()).map(|t| t.to_rfc3339());
+                conn.execute(
+                    "UPDATE schedules SET last_run = ?1, next_run = ?2 WHERE id = ?3",
+                    params![now_str, next_run, schedule.id],
+                )?;
+            } else {
+                conn.execute(
+                    "UPDATE schedules SET last_run = ?1, next_run = NULL, enabled = 0 WHERE id = ?2",
+                    params![now_str, schedule.id],
+                )?;
+            }
+        }
+
+        conn.execute_batch("COMMIT")?;
+        Ok(due)
+    }
+
+    /// Record that `schedule_id` ran and what its next occurrence should
+    /// be, independent of the automatic rescheduling `claim_due_schedules`
+    /// already performs — e.g. when a caller wants to override the
+    /// computed `next_run` (skipping an occurrence, or retrying sooner
+    /// after the dispatched task itself failed).
+    fn record_schedule_run(&self, schedule_id: &str, next_run: Option<&str>) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        self.conn()?.execute(
+            "UPDATE schedules SET last_run = ?1, next_run = ?2 WHERE id = ?3",
+            params![now, next_run, schedule_id],
+        )?;
+        Ok(())
+    }
+
     // ========================================================================
     // Trigger Operations
     // ========================================================================
 
-    pub fn create_trigger(
+    fn create_trigger(
         &self,
         agent_id: &str,
         name: &str,
@@ -479,7 +991,7 @@ impl Database {
         let config_json = serde_json::to_string(&config).unwrap_or_else(|_| "{}".to_string());
         let input_json = serde_json::to_string(&task_input).unwrap_or_else(|_| "{}".to_string());
 
-        self.conn.execute(
+        self.conn()?.execute(
             "INSERT INTO triggers (id, agent_id, name, trigger_type, config, task_type, task_input, created_at)
              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
             params![id, agent_id, name, trigger_type, config_json, task_type, input_json, now],
@@ -501,7 +1013,7 @@ impl Database {
         })
     }
 
-    pub fn get_triggers(&self, agent_id: Option<&str>) -> Result<Vec<triggers::Trigger>> {
+    fn get_triggers(&self, agent_id: Option<&str>) -> Result<Vec<triggers::Trigger>> {
         let sql = if agent_id.is_some() {
             "SELECT id, agent_id, name, trigger_type, config, task_type, task_input, enabled, last_triggered, created_at
              FROM triggers WHERE agent_id = ?1 ORDER BY created_at DESC"
@@ -510,37 +1022,20 @@ impl Database {
              FROM triggers ORDER BY created_at DESC"
         };
 
-        let mut stmt = self.conn.prepare(sql)?;
-        
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(sql)?;
+
         let triggers = if let Some(aid) = agent_id {
-            stmt.query_map(params![aid], Self::row_to_trigger)?
+            stmt.query_map(params![aid], Database::row_to_trigger)?
         } else {
-            stmt.query_map([], Self::row_to_trigger)?
-        }.collect::<Result<Vec<_>>>()?;
-        
-        Ok(triggers)
-    }
+            stmt.query_map([], Database::row_to_trigger)?
+        }.collect::<rusqlite::Result<Vec<_>>>()?;
 
-    fn row_to_trigger(row: &rusqlite::Row) -> Result<triggers::Trigger> {
-        let config_json: String = row.get(4)?;
-        let input_json: String = row.get(6)?;
-        
-        Ok(triggers::Trigger {
-            id: row.get(0)?,
-            agent_id: row.get(1)?,
-            name: row.get(2)?,
-            trigger_type: row.get(3)?,
-            config: serde_json::from_str(&config_json).unwrap_or(Value::Null),
-            task_type: row.get(5)?,
-            task_input: serde_json::from_str(&input_json).unwrap_or(Value::Null),
-            enabled: row.get::<_, i32>(7)? != 0,
-            last_triggered: row.get(8)?,
-            created_at: row.get(9)?,
-        })
+        Ok(triggers)
     }
 
-    pub fn delete_trigger(&self, trigger_id: &str) -> Result<()> {
-        self.conn.execute("DELETE FROM triggers WHERE id = ?1", params![trigger_id])?;
+    fn delete_trigger(&self, trigger_id: &str) -> Result<()> {
+        self.conn()?.execute("DELETE FROM triggers WHERE id = ?1", params![trigger_id])?;
         Ok(())
     }
 
@@ -548,7 +1043,7 @@ impl Database {
     // Activity Log Operations
     // ========================================================================
 
-    pub fn log_activity(
+    fn log_activity(
         &self,
         agent_id: Option<&str>,
         task_id: Option<&str>,
@@ -558,7 +1053,7 @@ impl Database {
         let id = Uuid::new_v4().to_string();
         let now = Utc::now().to_rfc3339();
 
-        self.conn.execute(
+        self.conn()?.execute(
             "INSERT INTO activity_log (id, agent_id, task_id, action, details, timestamp)
              VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
             params![id, agent_id, task_id, action, details, now],
@@ -567,7 +1062,7 @@ impl Database {
         Ok(())
     }
 
-    pub fn get_activity_log(
+    fn get_activity_log(
         &self,
         agent_id: Option<&str>,
         limit: u32,
@@ -580,8 +1075,9 @@ impl Database {
              FROM activity_log ORDER BY timestamp DESC LIMIT ?1"
         };
 
-        let mut stmt = self.conn.prepare(sql)?;
-        
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(sql)?;
+
         let logs = if let Some(aid) = agent_id {
             stmt.query_map(params![aid, limit], |row| {
                 Ok(tasks::ActivityLogEntry {
@@ -604,27 +1100,79 @@ impl Database {
                     timestamp: row.get(5)?,
                 })
             })?
-        }.collect::<Result<Vec<_>>>()?;
-        
+        }.collect::<rusqlite::Result<Vec<_>>>()?;
+
         Ok(logs)
     }
 
-    pub fn get_task_stats(&self, agent_id: Option<&str>) -> Result<tasks::TaskStats> {
+    /// A more general form of `get_activity_log`: every field of `filter`
+    /// narrows the query further, so callers that only care about one
+    /// dimension (an agent, a task, an action prefix, a time window) don't
+    /// need to filter the full log client-side.
+    fn get_activity(&self, filter: tasks::ActivityFilter) -> Result<Vec<tasks::ActivityLogEntry>> {
+        let mut sql = "SELECT id, agent_id, task_id, action, details, timestamp
+                       FROM activity_log WHERE 1=1".to_string();
+        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(agent_id) = &filter.agent_id {
+            sql.push_str(" AND agent_id = ?");
+            values.push(Box::new(agent_id.clone()));
+        }
+        if let Some(task_id) = &filter.task_id {
+            sql.push_str(" AND task_id = ?");
+            values.push(Box::new(task_id.clone()));
+        }
+        if let Some(prefix) = &filter.action_prefix {
+            sql.push_str(" AND action LIKE ?");
+            values.push(Box::new(format!("{prefix}%")));
+        }
+        if let Some(from) = filter.from {
+            sql.push_str(" AND timestamp >= ?");
+            values.push(Box::new(from.to_rfc3339()));
+        }
+        if let Some(to) = filter.to {
+            sql.push_str(" AND timestamp <= ?");
+            values.push(Box::new(to.to_rfc3339()));
+        }
+        sql.push_str(" ORDER BY timestamp DESC LIMIT ?");
+        values.push(Box::new(filter.limit));
+
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&sql)?;
+        let logs = stmt.query_map(
+            rusqlite::params_from_iter(values.iter().map(|v| v.as_ref())),
+            |row| {
+                Ok(tasks::ActivityLogEntry {
+                    id: row.get(0)?,
+                    agent_id: row.get(1)?,
+                    task_id: row.get(2)?,
+                    action: row.get(3)?,
+                    details: row.get(4)?,
+                    timestamp: row.get(5)?,
+                })
+            },
+        )?.collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(logs)
+    }
+
+    fn get_task_stats(&self, agent_id: Option<&str>) -> Result<tasks::TaskStats> {
         let base_sql = if agent_id.is_some() {
             "SELECT status, COUNT(*) FROM tasks WHERE agent_id = ?1 GROUP BY status"
         } else {
             "SELECT status, COUNT(*) FROM tasks GROUP BY status"
         };
 
-        let mut stmt = self.conn.prepare(base_sql)?;
-        
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(base_sql)?;
+
         let mut stats = tasks::TaskStats::default();
-        
+
         let rows: Vec<(String, i64)> = if let Some(aid) = agent_id {
             stmt.query_map(params![aid], |row| Ok((row.get(0)?, row.get(1)?)))?
         } else {
             stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
-        }.collect::<Result<Vec<_>>>()?;
+        }.collect::<rusqlite::Result<Vec<_>>>()?;
 
         for (status, count) in rows {
             match status.as_str() {
@@ -639,52 +1187,416 @@ impl Database {
         }
 
         stats.total = stats.pending + stats.running + stats.completed + stats.failed + stats.cancelled + stats.scheduled;
-        
+
+        let dead_sql = if agent_id.is_some() {
+            "SELECT COUNT(*) FROM tasks WHERE agent_id = ?1 AND status = 'failed' AND retry_count >= max_retries"
+        } else {
+            "SELECT COUNT(*) FROM tasks WHERE status = 'failed' AND retry_count >= max_retries"
+        };
+        let dead: i64 = if let Some(aid) = agent_id {
+            conn.query_row(dead_sql, params![aid], |row| row.get(0))?
+        } else {
+            conn.query_row(dead_sql, [], |row| row.get(0))?
+        };
+        stats.dead = dead as u32;
+
         Ok(stats)
     }
 
+    /// Group completed/failed tasks into `bucket`-wide time buckets over
+    /// `[from, to]`, via `strftime` on `completed_at` — the success-rate and
+    /// latency trend a flat `get_task_stats` count can't express. Duration
+    /// is `completed_at - started_at` in seconds, averaged over completed
+    /// tasks in the bucket only (a failed task's partial runtime isn't a
+    /// meaningful latency sample).
+    fn get_task_throughput(
+        &self,
+        agent_id: Option<&str>,
+        bucket: tasks::Granularity,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<(String, u32, u32, Option<f64>)>> {
+        // `bucket.strftime_format()` is one of our own two literals, not
+        // user input, so formatting it into the SQL is safe.
+        let mut sql = format!(
+            "SELECT strftime('{}', completed_at) AS bucket,
+                    SUM(CASE WHEN status = 'completed' THEN 1 ELSE 0 END),
+                    SUM(CASE WHEN status = 'failed' THEN 1 ELSE 0 END),
+                    AVG(CASE WHEN status = 'completed' AND started_at IS NOT NULL
+                             THEN (julianday(completed_at) - julianday(started_at)) * 86400.0 END)
+             FROM tasks
+             WHERE status IN ('completed', 'failed')
+               AND completed_at IS NOT NULL
+               AND completed_at >= ?1 AND completed_at <= ?2",
+            bucket.strftime_format(),
+        );
+        if agent_id.is_some() {
+            sql.push_str(" AND agent_id = ?3");
+        }
+        sql.push_str(" GROUP BY bucket ORDER BY bucket ASC");
+
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&sql)?;
+        let from_str = from.to_rfc3339();
+        let to_str = to.to_rfc3339();
+
+        let buckets = if let Some(aid) = agent_id {
+            stmt.query_map(params![from_str, to_str, aid], Database::row_to_throughput_bucket)?
+        } else {
+            stmt.query_map(params![from_str, to_str], Database::row_to_throughput_bucket)?
+        }.collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(buckets)
+    }
+
     // ========================================================================
     // Approval Operations
     // ========================================================================
 
-    pub fn get_pending_approvals(&self) -> Result<Vec<tasks::ApprovalRequest>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, agent_id, task_id, action_type, action_details, risk_level, status, created_at
+    /// Insert a new approval request, first running it past `self.policy`
+    /// (if configured). `AutoApprove`/`AutoReject` short-circuit straight
+    /// into the decided state with `decided_by` recording which rule (or
+    /// the default) made the call, instead of ever sitting pending; only
+    /// `RequireHuman` — or no policy at all — inserts a row awaiting a
+    /// human decision, as this did before the policy layer existed.
+    fn create_approval_request(
+        &self,
+        agent_id: &str,
+        task_id: Option<&str>,
+        action_type: &str,
+        action_details: Value,
+        risk_level: &str,
+    ) -> Result<tasks::ApprovalRequest> {
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now().to_rfc3339();
+        let details_json = serde_json::to_string(&action_details).unwrap_or_else(|_| "{}".to_string());
+
+        let parsed_risk: tasks::RiskLevel =
+            serde_json::from_value(Value::String(risk_level.to_lowercase())).unwrap_or(tasks::RiskLevel::Medium);
+        let decision = self
+            .policy
+            .as_ref()
+            .map(|policy| policy.evaluate(action_type, agent_id, parsed_risk));
+
+        let (status, decision_value, decided_at, decided_by) = match &decision {
+            Some(decision) if decision.outcome != PolicyOutcome::RequireHuman => {
+                let status = match decision.outcome {
+                    PolicyOutcome::AutoApprove => "approved",
+                    PolicyOutcome::AutoReject => "rejected",
+                    PolicyOutcome::RequireHuman => unreachable!(),
+                };
+                (status.to_string(), Some(status.to_string()), Some(now.clone()), decision.decided_by.clone())
+            }
+            _ => ("pending".to_string(), None, None, None),
+        };
+
+        self.conn()?.execute(
+            "INSERT INTO approval_requests
+                (id, agent_id, task_id, action_type, action_details, risk_level, status, decision, created_at, decided_at, decided_by)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                id, agent_id, task_id, action_type, details_json, risk_level, status, decision_value, now,
+                decided_at, decided_by
+            ],
+        )?;
+
+        self.log_activity(
+            Some(agent_id),
+            task_id,
+            if status == "pending" { "approval_requested" } else { "approval_auto_decided" },
+            Some(&format!(
+                "{} ({}) {}",
+                action_type,
+                risk_level,
+                if status == "pending" { "needs approval".to_string() } else { format!("{status} by {}", decided_by.as_deref().unwrap_or("policy")) }
+            )),
+        )?;
+
+        let request = tasks::ApprovalRequest {
+            id,
+            agent_id: agent_id.to_string(),
+            task_id: task_id.map(|s| s.to_string()),
+            action_type: action_type.to_string(),
+            action_details,
+            risk_level: risk_level.to_string(),
+            status: status.clone(),
+            created_at: now,
+            decided_at,
+            decided_by,
+        };
+
+        if let Some(notifier) = &self.notifier {
+            if status == "pending" {
+                notifier.notify(ApprovalEvent::Requested(&request));
+            } else {
+                notifier.notify(ApprovalEvent::Decided { request: &request, approved: status == "approved" });
+            }
+        }
+
+        Ok(request)
+    }
+
+    fn get_pending_approvals(&self) -> Result<Vec<tasks::ApprovalRequest>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, agent_id, task_id, action_type, action_details, risk_level, status, created_at, decided_at, decided_by
              FROM approval_requests WHERE status = 'pending' ORDER BY created_at DESC"
         )?;
 
-        let approvals = stmt.query_map([], |row| {
-            let details_json: String = row.get(4)?;
-            Ok(tasks::ApprovalRequest {
-                id: row.get(0)?,
-                agent_id: row.get(1)?,
-                task_id: row.get(2)?,
-                action_type: row.get(3)?,
-                action_details: serde_json::from_str(&details_json).unwrap_or(Value::Null),
-                risk_level: row.get(5)?,
-                status: row.get(6)?,
-                created_at: row.get(7)?,
-            })
-        })?.collect::<Result<Vec<_>>>()?;
+        let approvals = stmt.query_map([], Database::row_to_approval)?.collect::<rusqlite::Result<Vec<_>>>()?;
 
         Ok(approvals)
     }
 
-    pub fn process_approval(
+    /// List approvals, optionally narrowed to one `status`. The "all vs. one
+    /// status" choice is expressed as a single bound filter-enabled flag
+    /// (`?1`) alongside the status value (`?2`) rather than branching into
+    /// two separate SQL strings, so one statement covers both cases.
+    fn list_approvals_by_status(&self, status: Option<&str>) -> Result<Vec<tasks::ApprovalRequest>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, agent_id, task_id, action_type, action_details, risk_level, status, created_at, decided_at, decided_by
+             FROM approval_requests
+             WHERE (?1 = 0 OR status = ?2)
+             ORDER BY created_at DESC"
+        )?;
+
+        let filter_enabled = status.is_some();
+        let approvals = stmt
+            .query_map(params![filter_enabled, status.unwrap_or("")], Database::row_to_approval)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(approvals)
+    }
+
+    /// Paginated, filtered, and optionally column-projected approval
+    /// listing, for dashboards paging through a large backlog without
+    /// deserializing every `action_details` blob on every page.
+    fn list_approvals(&self, query: tasks::ListApprovalsQuery) -> Result<Vec<tasks::ApprovalRequest>> {
+        let projected: Vec<&str> = match &query.fields {
+            Some(fields) if !fields.is_empty() => APPROVAL_COLUMNS
+                .iter()
+                .filter(|column| fields.iter().any(|f| f == *column))
+                .copied()
+                .collect(),
+            _ => APPROVAL_COLUMNS.to_vec(),
+        };
+        let mut columns = projected;
+        if !columns.contains(&"id") {
+            columns.insert(0, "id");
+        }
+
+        let mut sql = format!("SELECT {} FROM approval_requests WHERE 1=1", columns.join(", "));
+        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(status) = &query.status {
+            sql.push_str(" AND status = ?");
+            values.push(Box::new(status.clone()));
+        }
+        if let Some(agent_id) = &query.agent_id {
+            sql.push_str(" AND agent_id = ?");
+            values.push(Box::new(agent_id.clone()));
+        }
+        if let Some(risk_level) = &query.risk_level {
+            sql.push_str(" AND risk_level = ?");
+            values.push(Box::new(risk_level.clone()));
+        }
+        sql.push_str(" ORDER BY created_at DESC LIMIT ? OFFSET ?");
+        values.push(Box::new(query.limit));
+        values.push(Box::new(query.offset));
+
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&sql)?;
+        let present: HashSet<&str> = columns.iter().copied().collect();
+        let approvals = stmt
+            .query_map(rusqlite::params_from_iter(values.iter().map(|v| v.as_ref())), |row| {
+                Database::row_to_projected_approval(row, &present)
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(approvals)
+    }
+
+    /// Compare-and-set the decision: the `WHERE` clause only matches if the
+    /// request is still at `expected_status` (normally `"pending"`), so two
+    /// concurrent reviewers — or a client retrying a timed-out request —
+    /// can't both "win" and silently overwrite each other's decision. A
+    /// zero-row update means someone else already decided it first.
+    fn process_approval(
         &self,
         approval_id: &str,
         approved: bool,
         modified_input: Option<Value>,
+        expected_status: &str,
     ) -> Result<()> {
         let now = Utc::now().to_rfc3339();
         let decision = if approved { "approved" } else { "rejected" };
         let modified_json = modified_input.map(|m| serde_json::to_string(&m).unwrap_or_default());
 
-        self.conn.execute(
-            "UPDATE approval_requests SET status = ?1, decision = ?2, modified_input = ?3, decided_at = ?4 WHERE id = ?5",
-            params![decision, decision, modified_json, now, approval_id],
+        let conn = self.conn()?;
+        let rows_affected = conn.execute(
+            "UPDATE approval_requests SET status = ?1, decision = ?2, modified_input = ?3, decided_at = ?4, decided_by = 'human'
+             WHERE id = ?5 AND status = ?6",
+            params![decision, decision, modified_json, now, approval_id, expected_status],
         )?;
 
+        if rows_affected == 0 {
+            return Err(DatabaseError::ApprovalConflict(approval_id.to_string()));
+        }
+
+        // Best-effort: if the notifier hook can't re-read the row back, the
+        // decision itself has still landed, so this doesn't fail the call.
+        let decided: Option<tasks::ApprovalRequest> = conn.query_row(
+            "SELECT id, agent_id, task_id, action_type, action_details, risk_level, status, created_at, decided_at, decided_by
+             FROM approval_requests WHERE id = ?1",
+            params![approval_id],
+            Database::row_to_approval,
+        ).ok();
+        drop(conn);
+
+        if let (Some(notifier), Some(request)) = (&self.notifier, &decided) {
+            notifier.notify(ApprovalEvent::Decided { request, approved });
+        }
+
         Ok(())
     }
+
+    /// The current schema version, tracked via SQLite's built-in
+    /// `PRAGMA user_version` integer rather than a tracking table, since it
+    /// costs nothing to query and survives a brand-new (version 0) database.
+    fn current_schema_version(&self) -> Result<u32> {
+        let conn = self.conn()?;
+        Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+    }
+}
+
+/// The outcome of dispatching a single due schedule as a task.
+pub struct ScheduleDispatchOutcome {
+    pub schedule_name: String,
+    pub result: Result<tasks::Task>,
+}
+
+/// Claim whatever schedules are due as of `now` and turn each into a new
+/// task, the same way a user-created task would start. Takes `store` as a
+/// `&dyn Store` (rather than `&Database` directly) so the scheduler's
+/// dispatch logic can be exercised against a `MockStore` instead of a real
+/// SQLite file; `start_schedule_loop` in `main.rs` is the only real caller.
+/// One schedule failing to dispatch doesn't stop the rest — each is
+/// reported in its own `ScheduleDispatchOutcome` for the caller to log.
+pub fn dispatch_due_schedules(store: &dyn Store, now: DateTime<Utc>) -> Result<Vec<ScheduleDispatchOutcome>> {
+    let due = store.claim_due_schedules(now)?;
+    Ok(due
+        .into_iter()
+        .map(|schedule| ScheduleDispatchOutcome {
+            result: store.create_task(&schedule.agent_id, &schedule.task_type, schedule.task_input.clone(), None),
+            schedule_name: schedule.name,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockall::predicate::eq;
+
+    fn sample_schedule(name: &str, agent_id: &str, task_type: &str) -> scheduler::Schedule {
+        scheduler::Schedule {
+            id: "sched-1".to_string(),
+            agent_id: agent_id.to_string(),
+            name: name.to_string(),
+            cron_expr: Some("0 9 * * *".to_string()),
+            run_at: None,
+            task_type: task_type.to_string(),
+            task_input: serde_json::json!({ "foo": "bar" }),
+            enabled: true,
+            last_run: None,
+            next_run: Some("2026-07-30T09:00:00Z".to_string()),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            timezone: None,
+            catch_up_missed: true,
+        }
+    }
+
+    fn sample_task(agent_id: &str, task_type: &str) -> tasks::Task {
+        tasks::Task {
+            id: "task-1".to_string(),
+            agent_id: agent_id.to_string(),
+            task_type: task_type.to_string(),
+            input: serde_json::json!({ "foo": "bar" }),
+            status: "pending".to_string(),
+            result: None,
+            error: None,
+            scheduled_at: None,
+            started_at: None,
+            completed_at: None,
+            created_at: "2026-07-30T09:00:00Z".to_string(),
+            retry_count: 0,
+            max_retries: 3,
+            next_retry_at: None,
+            attempt_timestamps: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn dispatch_due_schedules_creates_a_task_per_due_schedule() {
+        let now = DateTime::parse_from_rfc3339("2026-07-30T09:00:00Z").unwrap().with_timezone(&Utc);
+        let mut store = MockStore::new();
+        store
+            .expect_claim_due_schedules()
+            .with(eq(now))
+            .times(1)
+            .returning(move |_| Ok(vec![sample_schedule("daily-digest", "agent-1", "digest")]));
+        store
+            .expect_create_task()
+            .with(eq("agent-1"), eq("digest"), eq(serde_json::json!({ "foo": "bar" })), eq(None::<String>))
+            .times(1)
+            .returning(|agent_id, task_type, _input, _scheduled_at| Ok(sample_task(agent_id, task_type)));
+
+        let outcomes = dispatch_due_schedules(&store, now).expect("dispatch should succeed");
+
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].schedule_name, "daily-digest");
+        let task = outcomes[0].result.as_ref().expect("task should have been created");
+        assert_eq!(task.agent_id, "agent-1");
+        assert_eq!(task.task_type, "digest");
+    }
+
+    #[test]
+    fn dispatch_due_schedules_reports_a_failed_dispatch_without_failing_the_rest() {
+        let now = DateTime::parse_from_rfc3339("2026-07-30T09:00:00Z").unwrap().with_timezone(&Utc);
+        let mut store = MockStore::new();
+        store.expect_claim_due_schedules().returning(move |_| {
+            Ok(vec![
+                sample_schedule("broken-digest", "agent-missing", "digest"),
+                sample_schedule("ok-digest", "agent-1", "digest"),
+            ])
+        });
+        store.expect_create_task().times(2).returning(|agent_id, task_type, _input, _scheduled_at| {
+            if agent_id == "agent-missing" {
+                Err(DatabaseError::ApprovalConflict("agent-missing does not exist".to_string()))
+            } else {
+                Ok(sample_task(agent_id, task_type))
+            }
+        });
+
+        let outcomes = dispatch_due_schedules(&store, now).expect("claim succeeded, so dispatch returns outcomes");
+
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(outcomes[0].schedule_name, "broken-digest");
+        assert!(outcomes[0].result.is_err());
+        assert_eq!(outcomes[1].schedule_name, "ok-digest");
+        assert!(outcomes[1].result.is_ok());
+    }
+
+    #[test]
+    fn dispatch_due_schedules_propagates_a_claim_failure() {
+        let now = DateTime::parse_from_rfc3339("2026-07-30T09:00:00Z").unwrap().with_timezone(&Utc);
+        let mut store = MockStore::new();
+        store
+            .expect_claim_due_schedules()
+            .returning(|_| Err(DatabaseError::ApprovalConflict("unused".to_string())));
+
+        let err = dispatch_due_schedules(&store, now).expect_err("a claim failure should short-circuit dispatch");
+        assert!(matches!(err, DatabaseError::ApprovalConflict(_)));
+    }
 }
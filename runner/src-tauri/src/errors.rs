@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+/// Structured failure reason for a task, trigger, or approval flow. Each
+/// variant serializes into the existing `Option<Value>` / `error:
+/// Option<String>` fields so the shape on disk doesn't change, but the
+/// retry and approval machinery can now match on intent instead of
+/// re-parsing free text.
+#[derive(Debug, Clone, Serialize, Deserialize, Error, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TaskError {
+    #[error("task timed out after {timeout_ms}ms")]
+    Timeout { timeout_ms: u64 },
+
+    #[error("tool '{tool}' failed: {detail}")]
+    ToolFailure {
+        tool: String,
+        detail: String,
+        /// Whether this failure is likely to succeed on retry (e.g. a
+        /// network blip) as opposed to a deterministic tool bug.
+        #[serde(default)]
+        transient: bool,
+    },
+
+    #[error("approval denied: {reason}")]
+    ApprovalDenied { reason: String },
+
+    #[error("throttled: {reason}")]
+    ThrottleRejected { reason: String },
+
+    #[error("trigger condition failed: {condition}")]
+    TriggerConditionFailed { condition: String },
+
+    #[error("irrecoverable error: {detail}")]
+    Irrecoverable { detail: String },
+}
+
+impl TaskError {
+    /// Whether the retry machinery should consume a retry attempt for this
+    /// variant. `false` means fail immediately regardless of
+    /// `retry_count`/`max_retries`.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            TaskError::Timeout { .. } => true,
+            TaskError::ToolFailure { transient, .. } => *transient,
+            TaskError::ApprovalDenied { .. }
+            | TaskError::ThrottleRejected { .. }
+            | TaskError::TriggerConditionFailed { .. }
+            | TaskError::Irrecoverable { .. } => false,
+        }
+    }
+
+    /// Render into the legacy `error: Option<String>` column for backward
+    /// compatibility with anything that only reads the free-text message.
+    pub fn to_legacy_string(&self) -> String {
+        self.to_string()
+    }
+
+    /// Serialize into a structured `Value`, suitable for the task's
+    /// `result`/metadata column alongside the legacy string.
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    /// Recover a `TaskError` from a stored value, falling back to wrapping
+    /// a plain legacy string as `Irrecoverable` so old rows still parse.
+    pub fn from_value_or_string(value: Option<&Value>, legacy: Option<&str>) -> Option<Self> {
+        if let Some(value) = value {
+            if let Ok(parsed) = serde_json::from_value::<TaskError>(value.clone()) {
+                return Some(parsed);
+            }
+        }
+        legacy.map(|detail| TaskError::Irrecoverable { detail: detail.to_string() })
+    }
+}
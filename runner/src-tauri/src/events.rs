@@ -0,0 +1,237 @@
+use std::io::{self, Read};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use base64::Engine;
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+use tiny_http::{Header, Request, Response, StatusCode};
+use tungstenite::{Message, WebSocket};
+
+use crate::tasks::TaskStatus;
+use crate::triggers::TriggerEvent;
+
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// A single item published onto the event bus.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BusEvent {
+    TaskStatus {
+        task_id: String,
+        agent_id: String,
+        task_type: String,
+        status: TaskStatus,
+    },
+    Trigger {
+        agent_id: String,
+        task_type: String,
+        #[serde(flatten)]
+        event: TriggerEvent,
+    },
+}
+
+impl BusEvent {
+    fn agent_id(&self) -> &str {
+        match self {
+            BusEvent::TaskStatus { agent_id, .. } => agent_id,
+            BusEvent::Trigger { agent_id, .. } => agent_id,
+        }
+    }
+
+    fn task_type(&self) -> &str {
+        match self {
+            BusEvent::TaskStatus { task_type, .. } => task_type,
+            BusEvent::Trigger { task_type, .. } => task_type,
+        }
+    }
+
+    /// Serialize this event as a single SSE `data:` frame (including the
+    /// trailing blank line that terminates an SSE record).
+    pub fn to_sse_frame(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string());
+        format!("data: {json}\n\n")
+    }
+}
+
+/// Filter applied to a subscription so a client only receives the events it
+/// cares about. `None` on either field means "don't filter on this".
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    pub agent_id: Option<String>,
+    pub task_type: Option<String>,
+}
+
+impl EventFilter {
+    pub fn from_query(agent_id: Option<String>, task_type: Option<String>) -> Self {
+        Self { agent_id, task_type }
+    }
+
+    fn matches(&self, event: &BusEvent) -> bool {
+        if let Some(agent_id) = &self.agent_id {
+            if agent_id != event.agent_id() {
+                return false;
+            }
+        }
+        if let Some(task_type) = &self.task_type {
+            if task_type != event.task_type() {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+struct Subscriber {
+    sender: Sender<BusEvent>,
+    filter: EventFilter,
+}
+
+/// Fan-out hub that publishes task-status transitions and trigger events to
+/// any number of SSE/WebSocket subscribers. A sender whose receiver has
+/// disconnected is dropped the next time an event matching its filter is
+/// published; a subscription whose filter matches no later event stays
+/// registered.
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new subscriber and return the receiving end of its channel.
+    pub fn subscribe(&self, filter: EventFilter) -> Receiver<BusEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(Subscriber { sender, filter });
+        receiver
+    }
+
+    pub fn publish(&self, event: BusEvent) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|sub| {
+            if !sub.filter.matches(&event) {
+                return true;
+            }
+            sub.sender.send(event.clone()).is_ok()
+        });
+    }
+
+    pub fn publish_task_status(&self, task_id: &str, agent_id: &str, task_type: &str, status: TaskStatus) {
+        self.publish(BusEvent::TaskStatus {
+            task_id: task_id.to_string(),
+            agent_id: agent_id.to_string(),
+            task_type: task_type.to_string(),
+            status,
+        });
+    }
+
+    pub fn publish_trigger(&self, agent_id: &str, task_type: &str, event: TriggerEvent) {
+        self.publish(BusEvent::Trigger {
+            agent_id: agent_id.to_string(),
+            task_type: task_type.to_string(),
+            event,
+        });
+    }
+}
+
+/// Adapts a `Receiver<BusEvent>` into a blocking `Read` that yields one SSE
+/// `data:` frame per event, so it can be handed straight to `tiny_http` as a
+/// chunked response body.
+struct SseReader {
+    receiver: Receiver<BusEvent>,
+    pending: Vec<u8>,
+}
+
+impl Read for SseReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            let event = self
+                .receiver
+                .recv()
+                .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "subscriber closed"))?;
+            self.pending = event.to_sse_frame().into_bytes();
+        }
+
+        let n = buf.len().min(self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+}
+
+/// Serve a Server-Sent-Events stream of bus events matching `filter` until
+/// the subscriber disconnects. `security_headers` carries the same
+/// CORS-allowlist/hardening headers applied to every other MCP server
+/// response.
+pub fn serve_sse(request: Request, bus: &EventBus, filter: EventFilter, security_headers: Vec<Header>) {
+    let receiver = bus.subscribe(filter);
+    let reader = SseReader { receiver, pending: Vec::new() };
+
+    let mut headers = vec![
+        Header::from_bytes("Content-Type", "text/event-stream").unwrap(),
+        Header::from_bytes("Cache-Control", "no-cache").unwrap(),
+    ];
+    headers.extend(security_headers);
+
+    let response = Response::new(StatusCode(200), headers, reader, None, None);
+    let _ = request.respond(response);
+}
+
+fn websocket_accept_key(client_key: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(client_key.as_bytes());
+    hasher.update(WEBSOCKET_GUID.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(hasher.finalize())
+}
+
+/// Upgrade the connection to a WebSocket and push bus events matching
+/// `filter` as JSON text frames until the client disconnects.
+pub fn serve_websocket(request: Request, bus: &EventBus, filter: EventFilter) {
+    let Some(client_key) = request
+        .headers()
+        .iter()
+        .find(|h| h.field.as_str().as_str().eq_ignore_ascii_case("Sec-WebSocket-Key"))
+        .map(|h| h.value.as_str().to_string())
+    else {
+        let _ = request.respond(Response::empty(StatusCode(400)));
+        return;
+    };
+
+    let accept_key = websocket_accept_key(&client_key);
+    let handshake_response = Response::new(
+        StatusCode(101),
+        vec![
+            Header::from_bytes("Upgrade", "websocket").unwrap(),
+            Header::from_bytes("Sec-WebSocket-Accept", accept_key).unwrap(),
+        ],
+        io::empty(),
+        None,
+        None,
+    );
+
+    let stream = request.upgrade("websocket", handshake_response);
+    let mut socket = WebSocket::from_raw_socket(stream, tungstenite::protocol::Role::Server, None);
+    let receiver = bus.subscribe(filter);
+
+    loop {
+        match receiver.recv_timeout(Duration::from_secs(15)) {
+            Ok(event) => {
+                let json = serde_json::to_string(&event).unwrap_or_else(|_| "{}".to_string());
+                if socket.send(Message::Text(json)).is_err() {
+                    break;
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                // Idle ping keeps the connection alive and detects a dead peer.
+                if socket.send(Message::Ping(Vec::new())).is_err() {
+                    break;
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+}
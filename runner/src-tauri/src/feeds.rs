@@ -0,0 +1,63 @@
+use crate::tasks::ApprovalRequest;
+
+/// Render `approvals` (already filtered/ordered by the caller, e.g. via
+/// `Database::list_approvals_by_status`) as an Atom 1.0 feed, so operators
+/// can watch the approval queue from any feed reader instead of polling the
+/// `approval_requests` table themselves. `feed_url` is used as both the
+/// feed's self-link and its `<id>`, per the Atom spec's requirement that a
+/// feed identify itself.
+pub fn render_approval_feed(approvals: &[ApprovalRequest], feed_url: &str) -> String {
+    let updated = approvals
+        .iter()
+        .map(|a| a.decided_at.as_deref().unwrap_or(&a.created_at))
+        .max()
+        .map(str::to_string)
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str("  <title>AgentForge Approval Requests</title>\n");
+    xml.push_str(&format!("  <link href=\"{}\" rel=\"self\"/>\n", escape_xml(feed_url)));
+    xml.push_str(&format!("  <id>{}</id>\n", escape_xml(feed_url)));
+    xml.push_str(&format!("  <updated>{}</updated>\n", escape_xml(&updated)));
+
+    for approval in approvals {
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <id>urn:agentforge:approval:{}</id>\n", escape_xml(&approval.id)));
+        xml.push_str(&format!(
+            "    <title>{} [{}]</title>\n",
+            escape_xml(&approval.action_type),
+            escape_xml(&approval.risk_level),
+        ));
+        xml.push_str(&format!(
+            "    <updated>{}</updated>\n",
+            escape_xml(approval.decided_at.as_deref().unwrap_or(&approval.created_at)),
+        ));
+        xml.push_str(&format!("    <published>{}</published>\n", escape_xml(&approval.created_at)));
+        xml.push_str(&format!(
+            "    <summary>Agent {} — status: {}</summary>\n",
+            escape_xml(&approval.agent_id),
+            escape_xml(&approval.status),
+        ));
+        xml.push_str(&format!(
+            "    <content type=\"text\">{}</content>\n",
+            escape_xml(&approval.action_details.to_string()),
+        ));
+        xml.push_str("  </entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+/// Escape the five XML special characters so feed text (agent names, JSON
+/// action details, etc.) can't break out of an element or attribute.
+fn escape_xml(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
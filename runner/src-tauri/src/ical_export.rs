@@ -0,0 +1,179 @@
+use crate::scheduler::{Schedule, ScheduleTemplate};
+
+/// Fold a cron field's `*/N` step syntax into an `RRULE` `INTERVAL`, or
+/// `None` for a bare `*` (unconstrained, no `INTERVAL` needed).
+fn step_interval(field: &str) -> Option<u32> {
+    field.strip_prefix("*/").and_then(|n| n.parse().ok())
+}
+
+const WEEKDAY_CODES: [&str; 7] = ["SU", "MO", "TU", "WE", "TH", "FR", "SA"];
+
+/// Translate one cron day-of-week field value into its RFC 5545 `BYDAY`
+/// code. Cron's `0` and `7` both mean Sunday; only bare numeric weekdays
+/// are handled; anything else falls through to "every day".
+fn weekday_code(value: &str) -> Option<&'static str> {
+    let n: u32 = value.parse().ok()?;
+    Some(WEEKDAY_CODES[(n % 7) as usize])
+}
+
+/// Translate a 5-field cron expression into an RFC 5545 `RRULE` value
+/// (without the `RRULE:` prefix). Covers a `*/N` minute or hour step, a
+/// comma list of numeric weekdays (`FREQ=WEEKLY`), and otherwise falls back
+/// to `FREQ=MONTHLY` when the day-of-month or month field is constrained, or
+/// `FREQ=DAILY`, at the expression's literal minute and hour. Ranges and
+/// day-of-month/month values are not carried into the rule; an expression
+/// without exactly five fields becomes plain `FREQ=DAILY`.
+fn cron_to_rrule(cron_expr: &str) -> String {
+    let fields: Vec<&str> = cron_expr.split_whitespace().collect();
+    if fields.len() != 5 {
+        return "FREQ=DAILY".to_string();
+    }
+    let (minute, hour, day, month, weekday) = (fields[0], fields[1], fields[2], fields[3], fields[4]);
+
+    if let Some(interval) = step_interval(minute) {
+        return if interval == 1 {
+            "FREQ=MINUTELY".to_string()
+        } else {
+            format!("FREQ=MINUTELY;INTERVAL={interval}")
+        };
+    }
+    if let Some(interval) = step_interval(hour) {
+        return if interval == 1 {
+            "FREQ=HOURLY".to_string()
+        } else {
+            format!("FREQ=HOURLY;INTERVAL={interval}")
+        };
+    }
+    if weekday != "*" {
+        let days: Vec<&str> = weekday.split(',').filter_map(weekday_code).collect();
+        if !days.is_empty() {
+            return format!("FREQ=WEEKLY;BYDAY={};BYHOUR={hour};BYMINUTE={minute}", days.join(","));
+        }
+    }
+    if day != "*" || month != "*" {
+        return format!("FREQ=MONTHLY;BYHOUR={hour};BYMINUTE={minute}");
+    }
+    format!("FREQ=DAILY;BYHOUR={hour};BYMINUTE={minute}")
+}
+
+/// Render an RFC 3339 timestamp as an RFC 5545 UTC date-time
+/// (`YYYYMMDDTHHMMSSZ`), converting any offset to UTC and dropping
+/// fractional seconds.
+fn format_stamp(rfc3339: &str) -> String {
+    if let Ok(parsed) = chrono::DateTime::parse_from_rfc3339(rfc3339) {
+        return parsed.with_timezone(&chrono::Utc).format("%Y%m%dT%H%M%SZ").to_string();
+    }
+    rfc3339
+        .replace(['-', ':'], "")
+        .split('.')
+        .next()
+        .unwrap_or(rfc3339)
+        .trim_end_matches('Z')
+        .to_string()
+        + "Z"
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+        .replace('\n', "\\n")
+}
+
+fn schedule_vevent(schedule: &Schedule, now_stamp: &str) -> String {
+    let uid = format!("schedule-{}@agentforge", schedule.id);
+    let summary = escape_text(&schedule.name);
+    let description = escape_text(&format!("task_type: {}", schedule.task_type));
+
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{uid}"),
+        format!("DTSTAMP:{now_stamp}"),
+        format!("SUMMARY:{summary}"),
+        format!("DESCRIPTION:{description}"),
+    ];
+
+    if let Some(cron_expr) = &schedule.cron_expr {
+        let reference = schedule.last_run.as_deref().unwrap_or(&schedule.created_at);
+        lines.push(format!("DTSTART:{}", format_stamp(reference)));
+        lines.push(format!("RRULE:{}", cron_to_rrule(cron_expr)));
+    } else if let Some(run_at) = &schedule.run_at {
+        lines.push(format!("DTSTART:{}", format_stamp(run_at)));
+    } else {
+        lines.push(format!("DTSTART:{now_stamp}"));
+    }
+
+    lines.push("END:VEVENT".to_string());
+    lines.join("\r\n")
+}
+
+fn template_vevent(template: &ScheduleTemplate, now_stamp: &str) -> String {
+    let uid = format!("template-{}@agentforge", template.id);
+    let summary = escape_text(&template.name);
+    let description = escape_text(&format!("task_type: {}", template.task_type));
+
+    vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{uid}"),
+        format!("DTSTAMP:{now_stamp}"),
+        format!("SUMMARY:{summary}"),
+        format!("DESCRIPTION:{description}"),
+        format!("DTSTART:{now_stamp}"),
+        format!("RRULE:{}", cron_to_rrule(&template.cron_expr)),
+        "END:VEVENT".to_string(),
+    ]
+    .join("\r\n")
+}
+
+/// Render a VCALENDAR containing one VEVENT per schedule, suitable for
+/// subscribing to in a calendar app. `now` is the RFC3339 timestamp used
+/// for every `DTSTAMP` (the moment the feed was generated) — callers pass
+/// it in rather than this module calling `Utc::now()` itself, keeping it a
+/// pure function of its inputs.
+pub fn render_calendar(schedules: &[Schedule], now: &str) -> String {
+    let now_stamp = format_stamp(now);
+    let events: Vec<String> = schedules.iter().map(|s| schedule_vevent(s, &now_stamp)).collect();
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//AgentForge//Schedules//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:AgentForge Schedules".to_string(),
+        "REFRESH-INTERVAL;VALUE=DURATION:PT15M".to_string(),
+        "X-PUBLISHED-TTL:PT15M".to_string(),
+    ];
+    lines.extend(events);
+    lines.push("END:VCALENDAR".to_string());
+    lines.join("\r\n") + "\r\n"
+}
+
+/// Render the built-in `ScheduleTemplate`s (`get_schedule_templates`) as a
+/// calendar, so a user can preview a template's recurrence before creating
+/// a real schedule from it.
+pub fn render_template_calendar(templates: &[ScheduleTemplate], now: &str) -> String {
+    let now_stamp = format_stamp(now);
+    let events: Vec<String> = templates.iter().map(|t| template_vevent(t, &now_stamp)).collect();
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//AgentForge//ScheduleTemplates//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:AgentForge Schedule Templates".to_string(),
+    ];
+    lines.extend(events);
+    lines.push("END:VCALENDAR".to_string());
+    lines.join("\r\n") + "\r\n"
+}
+
+/// Tauri command surface: serialize the caller's schedules (passed as JSON
+/// rather than loaded from `AppState` directly, so this feed can be
+/// previewed/exported without depending on `Database`/`Store`) into a
+/// subscribable `.ics` feed.
+#[tauri::command]
+pub fn export_schedules_ics(schedules_json: String, now: String) -> Result<String, String> {
+    let schedules: Vec<Schedule> =
+        serde_json::from_str(&schedules_json).map_err(|e| format!("invalid schedules: {e}"))?;
+    Ok(render_calendar(&schedules, &now))
+}
@@ -0,0 +1,124 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+const LABEL: &str = "com.agentforge.launchatlogin";
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(PathBuf::from)
+}
+
+fn launchd_plist_path() -> Option<PathBuf> {
+    home_dir().map(|home| home.join("Library/LaunchAgents").join(format!("{LABEL}.plist")))
+}
+
+fn autostart_desktop_path() -> Option<PathBuf> {
+    home_dir().map(|home| home.join(".config/autostart/agentforge.desktop"))
+}
+
+const WINDOWS_RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+const WINDOWS_RUN_VALUE: &str = "AgentForge Runner";
+
+fn command_line(binary_path: &str, minimized: bool) -> String {
+    if minimized {
+        format!("{binary_path} --minimized")
+    } else {
+        binary_path.to_string()
+    }
+}
+
+/// Register `binary_path` as a login item so the scheduler daemon starts
+/// automatically. `minimized` passes `--minimized` through so the app
+/// comes up tray-resident rather than with a visible window.
+pub fn set_enabled(binary_path: &str, minimized: bool) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        let path = launchd_plist_path().ok_or("could not resolve home directory")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("failed to create LaunchAgents dir: {e}"))?;
+        }
+        let args = if minimized {
+            format!("<string>{binary_path}</string>\n        <string>--minimized</string>")
+        } else {
+            format!("<string>{binary_path}</string>")
+        };
+        let plist = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key><string>{LABEL}</string>
+    <key>ProgramArguments</key>
+    <array>
+        {args}
+    </array>
+    <key>RunAtLoad</key><true/>
+    <key>KeepAlive</key><false/>
+</dict>
+</plist>
+"#
+        );
+        fs::write(&path, plist).map_err(|e| format!("failed to write login item plist: {e}"))?;
+        let _ = Command::new("launchctl").arg("load").arg(&path).status();
+        return Ok(());
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let path = autostart_desktop_path().ok_or("could not resolve home directory")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("failed to create autostart dir: {e}"))?;
+        }
+        let exec = command_line(binary_path, minimized);
+        let desktop_entry = format!(
+            "[Desktop Entry]\nType=Application\nName=AgentForge Runner\nExec={exec}\nX-GNOME-Autostart-enabled=true\n"
+        );
+        fs::write(&path, desktop_entry).map_err(|e| format!("failed to write autostart entry: {e}"))?;
+        return Ok(());
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let command = command_line(binary_path, minimized);
+        let status = Command::new("reg")
+            .args(["add", WINDOWS_RUN_KEY, "/v", WINDOWS_RUN_VALUE, "/t", "REG_SZ", "/d", &command, "/f"])
+            .status();
+        return match status {
+            Ok(s) if s.success() => Ok(()),
+            Ok(s) => Err(format!("reg add exited with status {s}")),
+            Err(e) => Err(format!("failed to run reg add: {e}")),
+        };
+    }
+
+    #[allow(unreachable_code)]
+    Err("unsupported platform for launch-at-login".to_string())
+}
+
+/// Unregister whatever login item `set_enabled` created.
+pub fn unset_enabled() -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        if let Some(path) = launchd_plist_path() {
+            let _ = Command::new("launchctl").arg("unload").arg(&path).status();
+            let _ = fs::remove_file(&path);
+        }
+        return Ok(());
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(path) = autostart_desktop_path() {
+            let _ = fs::remove_file(&path);
+        }
+        return Ok(());
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = Command::new("reg").args(["delete", WINDOWS_RUN_KEY, "/v", WINDOWS_RUN_VALUE, "/f"]).status();
+        return Ok(());
+    }
+
+    #[allow(unreachable_code)]
+    Ok(())
+}
@@ -1,19 +1,72 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod backoff;
+mod bm25;
+mod brain_store;
+mod capabilities;
+mod credentials;
+mod database;
+mod errors;
+mod events;
+mod feeds;
+mod ical_export;
+mod launch_at_login;
+mod mcp;
+mod mcp_security;
+mod memory_tail;
+mod notifications;
+mod os_scheduler;
+mod policy;
+mod scheduler;
+mod spool;
+mod tasks;
+mod throttle;
+mod triggers;
+
+use std::collections::HashMap;
 use std::fs;
-use std::io::Write;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Duration;
 use tauri::{Emitter, Manager};
 use tauri::path::BaseDirectory;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use tiny_http::{Header, Method, Response, Server, StatusCode};
+use tiny_http::{Header, Method, Request, Response, Server, StatusCode};
 use keyring::Entry;
 
+#[cfg(feature = "embedded_python")]
+use pyo3::prelude::*;
+#[cfg(feature = "embedded_python")]
+use pyo3::types::PyModule;
+
+use brain_store::{build_brain_store, brain_backend_setting, AuditEntry, MemoryEntry, MemoryMatch};
+use database::{Database, Store};
+use events::{BusEvent, EventBus, EventFilter};
+use notifications::Notifier;
+use policy::Policy;
+
+/// A configured agent: its persona, model, and tool access. Persisted via
+/// `Store::save_agent`/`get_agents`/`get_agent` into the `agents` table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentConfig {
+    /// `None` for a not-yet-saved agent; `save_agent` fills in a fresh UUID
+    /// when this is `None` and returns the id it used.
+    pub id: Option<String>,
+    pub name: String,
+    pub goal: String,
+    pub personality: String,
+    pub provider: String,
+    pub model: String,
+    pub temperature: f64,
+    pub tools: Vec<String>,
+    pub autonomy_level: u8,
+}
+
 fn emit_config(app: &tauri::AppHandle, config_json: String) {
     let _ = app.emit("agentforge://config", config_json);
 }
@@ -34,14 +87,71 @@ fn try_load_agent_file(app: &tauri::AppHandle, path: &str) {
     }
 }
 
-fn spawn_backend(app: &tauri::AppHandle) {
-    let script_path = app
-        .path()
+fn resolve_agent_server_script(app: &tauri::AppHandle) -> Option<PathBuf> {
+    app.path()
         .resolve("python/agent_server.py", BaseDirectory::Resource)
         .or_else(|_| app.path().resolve("resources/python/agent_server.py", BaseDirectory::Resource))
-        .ok();
+        .ok()
+}
+
+/// Embed `agent_server.py` directly into this process via pyo3, in place
+/// of spawning it as a `python3`/`python` subprocess. Adds the script's
+/// own directory to `sys.path` so it imports as a regular module, then
+/// stashes the module handle on `AppState` so commands can call into it
+/// without re-spawning anything.
+#[cfg(feature = "embedded_python")]
+fn spawn_backend(app: &tauri::AppHandle) {
+    let Some(script_path) = resolve_agent_server_script(app) else {
+        let _ = app.emit(
+            "agentforge://error",
+            "Backend script not found. Please reinstall AgentForge Runner.".to_string(),
+        );
+        return;
+    };
+    let Some(script_dir) = script_path.parent() else {
+        let _ = app.emit(
+            "agentforge://error",
+            "Backend script path has no parent directory.".to_string(),
+        );
+        return;
+    };
+    let module_name = script_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("agent_server")
+        .to_string();
+
+    pyo3::prepare_freethreaded_python();
+    let embedded = Python::with_gil(|py| -> PyResult<Py<PyModule>> {
+        let sys_path = py.import_bound("sys")?.getattr("path")?;
+        sys_path.call_method1("insert", (0, script_dir.to_string_lossy().to_string()))?;
+        let module = PyModule::import_bound(py, module_name.as_str())?;
+        Ok(module.unbind())
+    });
+
+    match embedded {
+        Ok(module) => {
+            if let Some(state) = app.try_state::<AppState>() {
+                if let Ok(mut guard) = state.agent_server.lock() {
+                    *guard = Some(module);
+                }
+            }
+        }
+        Err(e) => {
+            let _ = app.emit(
+                "agentforge://error",
+                format!("Failed to embed Python backend: {e}"),
+            );
+        }
+    }
+}
 
-    let Some(script_path) = script_path else {
+/// Fallback model kept behind a feature flag for environments where
+/// embedding CPython isn't an option: shell out to a `python3`/`python`
+/// subprocess running the same `agent_server.py` script.
+#[cfg(all(feature = "subprocess_backend", not(feature = "embedded_python")))]
+fn spawn_backend(app: &tauri::AppHandle) {
+    let Some(script_path) = resolve_agent_server_script(app) else {
         let _ = app.emit(
             "agentforge://error",
             "Backend script not found. Please reinstall AgentForge Runner.".to_string(),
@@ -73,6 +183,51 @@ fn spawn_backend(app: &tauri::AppHandle) {
     );
 }
 
+/// Replaces the old port/health-poll-oriented backend status: reports the
+/// embedded interpreter's version and whether `agent_server` loaded.
+#[derive(Debug, Serialize, Deserialize)]
+struct BackendInfo {
+    embedded: bool,
+    python_version: String,
+    agent_server_loaded: bool,
+}
+
+#[tauri::command]
+fn get_backend_info(state: tauri::State<AppState>) -> BackendInfo {
+    #[cfg(feature = "embedded_python")]
+    {
+        let agent_server_loaded = state.agent_server.lock().map(|g| g.is_some()).unwrap_or(false);
+        let python_version = Python::with_gil(|py| py.version().to_string());
+        return BackendInfo {
+            embedded: true,
+            python_version,
+            agent_server_loaded,
+        };
+    }
+
+    #[cfg(not(feature = "embedded_python"))]
+    {
+        let _ = &state;
+        BackendInfo {
+            embedded: false,
+            python_version: "subprocess backend: version not tracked in-process".to_string(),
+            agent_server_loaded: false,
+        }
+    }
+}
+
+/// Build the `BrainStore` for `brain_path`, selecting the backend named in
+/// the brain folder's own `brain-settings.json` (defaulting to the
+/// original flat-file layout).
+fn open_brain_store(brain_path: &str) -> Result<Box<dyn brain_store::BrainStore>, String> {
+    if brain_path.trim().is_empty() {
+        return Err("Brain folder path is missing.".to_string());
+    }
+    let base = PathBuf::from(brain_path);
+    let backend = brain_backend_setting(&base);
+    build_brain_store(base, backend.as_deref())
+}
+
 #[tauri::command]
 fn save_brain_conversation(
     brain_path: String,
@@ -81,49 +236,8 @@ fn save_brain_conversation(
     file_name: String,
     contents: String,
 ) -> Result<(), String> {
-    if brain_path.trim().is_empty() {
-        return Err("Brain folder path is missing.".to_string());
-    }
-    let safe_agent = agent_name
-        .chars()
-        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
-        .collect::<String>();
-    let base = PathBuf::from(brain_path);
-    let target = base
-        .join("AgentForge Brain")
-        .join(safe_agent)
-        .join("conversations")
-        .join(date_folder);
-    fs::create_dir_all(&target).map_err(|e| format!("Failed to create brain folder: {e}"))?;
-    let file_path = target.join(file_name);
-    fs::write(&file_path, contents).map_err(|e| format!("Failed to write brain file: {e}"))?;
-    Ok(())
-}
-
-#[derive(Serialize, Deserialize)]
-struct MemoryEntry {
-    id: String,
-    role: String,
-    content: String,
-    timestamp: String,
-    #[serde(rename = "conversationId")]
-    conversation_id: String,
-}
-
-#[derive(Serialize)]
-struct MemoryMatch {
-    content: String,
-    role: String,
-    timestamp: String,
-}
-
-#[derive(Serialize, Deserialize)]
-struct AuditEntry {
-    action: String,
-    detail: String,
-    timestamp: String,
-    #[serde(rename = "conversationId")]
-    conversation_id: String,
+    let store = open_brain_store(&brain_path)?;
+    store.save_conversation(&agent_name, &date_folder, &file_name, &contents)
 }
 
 fn sanitize_agent_name(agent_name: &str) -> String {
@@ -147,8 +261,164 @@ fn mcp_settings_path(app: &tauri::AppHandle) -> Option<PathBuf> {
         .map(|dir| dir.join("mcp-settings.json"))
 }
 
+fn database_path(app: &tauri::AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(|dir| dir.join("agentforge.db"))
+}
+
+fn spool_dir(app: &tauri::AppHandle) -> Option<PathBuf> {
+    app.path().app_data_dir().ok().map(|dir| dir.join("spool"))
+}
+
+fn policy_path(app: &tauri::AppHandle) -> Option<PathBuf> {
+    app.path().app_data_dir().ok().map(|dir| dir.join("policy.json"))
+}
+
+/// Load the approval policy from `policy.json` in the app data directory,
+/// falling back to `Policy::default()` (require a human for every action,
+/// the same behavior as before this was configurable) if the file is
+/// missing or fails to parse.
+fn load_policy(app: &tauri::AppHandle) -> Policy {
+    policy_path(app)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Build the approval notifier from the `"notifications"` object in
+/// `mcp-settings.json` (the same file every other app-wide toggle already
+/// lives in). A desktop notification is included unless explicitly turned
+/// off; the webhook/email channels only appear once their target is
+/// configured, since there's no sane default URL for either.
+fn build_notifier(app: &tauri::AppHandle, settings: &Value) -> Option<Arc<Notifier>> {
+    let config = settings.get("notifications").cloned().unwrap_or_else(|| json!({}));
+    let mut channels: Vec<Box<dyn notifications::NotificationChannel>> = Vec::new();
+
+    if config.get("desktop").and_then(Value::as_bool).unwrap_or(true) {
+        channels.push(Box::new(notifications::DesktopChannel { app: app.clone() }));
+    }
+    if let Some(url) = config.get("webhookUrl").and_then(Value::as_str) {
+        channels.push(Box::new(notifications::WebhookChannel { url: url.to_string() }));
+    }
+    if let (Some(send_url), Some(to)) = (
+        config.get("emailSendUrl").and_then(Value::as_str),
+        config.get("emailTo").and_then(Value::as_str),
+    ) {
+        channels.push(Box::new(notifications::EmailChannel { send_url: send_url.to_string(), to: to.to_string() }));
+    }
+
+    if channels.is_empty() {
+        return None;
+    }
+    let cooldown_secs = config.get("cooldownSeconds").and_then(Value::as_u64).unwrap_or(300);
+    Some(Arc::new(Notifier::new(channels, Duration::from_secs(cooldown_secs))))
+}
+
+/// Best-effort native-scheduler sync for `schedule`: installs it (launchd/
+/// systemd/Task Scheduler, per `os_scheduler`) so it still fires while the
+/// app itself isn't running, or uninstalls it when disabled. The in-process
+/// `start_schedule_loop` tick remains the schedule's primary dispatch path;
+/// this is a supplementary when-the-app-is-closed path, so failures here
+/// are logged rather than propagated to the caller.
+fn sync_schedule_os(schedule: &scheduler::Schedule) {
+    if !schedule.enabled {
+        if let Err(e) = os_scheduler::uninstall(&schedule.id) {
+            eprintln!("Failed to uninstall native scheduler entry for '{}': {e}", schedule.name);
+        }
+        return;
+    }
+
+    let Ok(binary_path) = std::env::current_exe() else {
+        eprintln!("Failed to resolve current executable for native scheduler install of '{}'", schedule.name);
+        return;
+    };
+    if let Err(e) = os_scheduler::install(schedule, &binary_path.to_string_lossy()) {
+        eprintln!("Failed to install native scheduler entry for '{}': {e}", schedule.name);
+    }
+}
+
+/// Poll `db` once a minute for due schedules, claiming each atomically
+/// (`claim_due_schedules` also advances `next_run`/disables one-shots in
+/// the same transaction) and turning it into a task the normal task queue
+/// picks up, the same way a user-created task would. Also sweeps expired
+/// terminal tasks out of `spool` on the same cadence, since both are
+/// background maintenance that's fine to share a tick. Runs until the
+/// process exits.
+fn start_schedule_loop(db: Arc<Database>, spool: Arc<spool::TaskSpool>) {
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_secs(60));
+        let outcomes = match database::dispatch_due_schedules(db.as_ref(), chrono::Utc::now()) {
+            Ok(outcomes) => outcomes,
+            Err(e) => {
+                eprintln!("Failed to poll due schedules: {e}");
+                continue;
+            }
+        };
+        for outcome in outcomes {
+            match outcome.result {
+                Ok(task) => {
+                    if let Err(e) = spool.save(task) {
+                        eprintln!("Failed to spool dispatched task for schedule '{}': {e}", outcome.schedule_name);
+                    }
+                }
+                Err(e) => eprintln!("Failed to dispatch schedule '{}': {e}", outcome.schedule_name),
+            }
+        }
+        if let Err(e) = spool.sweep_expired() {
+            eprintln!("Failed to sweep expired spool entries: {e}");
+        }
+    });
+}
+
 struct AppState {
     run_in_background: Mutex<bool>,
+    launch_at_login: Mutex<bool>,
+    events: Arc<EventBus>,
+    mcp_tokens: Arc<mcp_security::TokenStore>,
+    db: Arc<Database>,
+    /// Durable on-disk mirror of in-flight tasks, kept alongside `db` so a
+    /// crash mid-task can still recover what was running. `database.rs` is
+    /// the source of truth; this exists purely for crash recovery, so a
+    /// `spool.save` failure here is logged rather than propagated.
+    spool: Arc<spool::TaskSpool>,
+    /// Per-agent concurrency/rate-limit quotas, checked by `claim_next_task`
+    /// before a claimed task is actually handed out and released once it
+    /// leaves `running` (a terminal status, or a failure rescheduled for
+    /// retry).
+    throttle: Arc<throttle::Throttle>,
+    /// Every `DeadLetterReport` `Database::fail_task` has produced for a
+    /// task that exhausted its retries, kept in memory so the UI can list
+    /// them via `get_dead_letters`.
+    dead_letter_sink: Arc<backoff::InMemoryDeadLetterSink>,
+    /// The embedded `agent_server` Python module, once `spawn_backend` has
+    /// loaded it in-process. `None` before `setup` runs, or if embedding
+    /// failed, or under the `subprocess_backend` fallback feature.
+    #[cfg(feature = "embedded_python")]
+    agent_server: Mutex<Option<Py<PyModule>>>,
+}
+
+/// Mint a new MCP bearer token scoped to `scopes` (tool names, or `"*"`
+/// for every tool) for a UI-initiated integration to use against the
+/// local MCP server.
+#[tauri::command]
+fn mint_mcp_token(state: tauri::State<AppState>, scopes: Vec<String>) -> String {
+    state.mcp_tokens.mint(scopes)
+}
+
+/// Parse `a=1&b=2` style query strings out of a raw request URL.
+fn parse_query(url: &str) -> HashMap<String, String> {
+    let Some(query) = url.split_once('?').map(|(_, q)| q) else {
+        return HashMap::new();
+    };
+    query
+        .split('&')
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
 }
 
 const KEYCHAIN_SERVICE: &str = "AgentForge Runner";
@@ -157,32 +427,80 @@ fn keychain_entry(key: &str) -> Result<Entry, keyring::Error> {
     Entry::new(KEYCHAIN_SERVICE, key)
 }
 
+/// Stores under `credentials::credential_backend()` (the OS keyring by
+/// default, falling back to the encrypted file vault when the keyring
+/// itself errors), rather than talking to `Entry` directly, so a headless
+/// environment with no secret service still persists secrets.
 #[tauri::command]
 fn set_secret(key: String, value: String) -> Result<(), String> {
-    let entry = keychain_entry(&key).map_err(|e| format!("Failed to open keychain: {e}"))?;
-    entry
-        .set_password(&value)
-        .map_err(|e| format!("Failed to save secret: {e}"))
+    credentials::store_credential(KEYCHAIN_SERVICE, &key, &value)
 }
 
 #[tauri::command]
 fn get_secret(key: String) -> Result<Option<String>, String> {
-    let entry = keychain_entry(&key).map_err(|e| format!("Failed to open keychain: {e}"))?;
-    match entry.get_password() {
-        Ok(value) => Ok(Some(value)),
-        Err(keyring::Error::NoEntry) => Ok(None),
-        Err(err) => Err(format!("Failed to read secret: {err}")),
-    }
+    credentials::get_credential(KEYCHAIN_SERVICE, &key)
 }
 
 #[tauri::command]
 fn delete_secret(key: String) -> Result<(), String> {
-    let entry = keychain_entry(&key).map_err(|e| format!("Failed to open keychain: {e}"))?;
-    match entry.delete_password() {
-        Ok(_) => Ok(()),
-        Err(keyring::Error::NoEntry) => Ok(()),
-        Err(err) => Err(format!("Failed to delete secret: {err}")),
+    credentials::delete_credential(KEYCHAIN_SERVICE, &key)
+}
+
+/// Every bundled and user-registered credential type (OpenAI, Gmail, ...),
+/// for the UI to drive its "connect a tool" form from data instead of a
+/// hard-coded field list per provider.
+#[tauri::command]
+fn list_credential_types() -> Vec<credentials::CredentialTypeDefinition> {
+    credentials::list_credential_types()
+}
+
+/// Add (or replace, by `id`) a credential type in the user-editable
+/// override manifest.
+#[tauri::command]
+fn register_credential_type(definition: credentials::CredentialTypeDefinition) -> Result<(), String> {
+    credentials::register_credential_type(definition)
+}
+
+/// Validate `values` against `tool_id`'s registered field schema without
+/// storing anything, so the UI can surface validation errors inline.
+#[tauri::command]
+fn validate_credential(tool_id: String, values: HashMap<String, String>) -> Result<(), String> {
+    credentials::validate_credential(&tool_id, &values)
+}
+
+/// Validate `values` against `tool_id`'s schema, then store each field
+/// keyed by `tool_id` so `get_tool_credential`/`delete_tool_credential`
+/// can find them again.
+#[tauri::command]
+fn store_tool_credential(tool_id: String, values: HashMap<String, String>) -> Result<(), String> {
+    credentials::validate_credential(&tool_id, &values)?;
+    for (field, value) in &values {
+        credentials::store_credential(&tool_id, field, value)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn get_tool_credential(tool_id: String) -> Result<HashMap<String, String>, String> {
+    let definition = credentials::get_tool_credential_type(&tool_id)
+        .ok_or_else(|| format!("unknown credential type '{tool_id}'"))?;
+    let mut values = HashMap::new();
+    for field in &definition.fields {
+        if let Some(value) = credentials::get_credential(&tool_id, &field.name)? {
+            values.insert(field.name.clone(), value);
+        }
+    }
+    Ok(values)
+}
+
+#[tauri::command]
+fn delete_tool_credential(tool_id: String) -> Result<(), String> {
+    let definition = credentials::get_tool_credential_type(&tool_id)
+        .ok_or_else(|| format!("unknown credential type '{tool_id}'"))?;
+    for field in &definition.fields {
+        credentials::delete_credential(&tool_id, &field.name)?;
     }
+    Ok(())
 }
 
 #[tauri::command]
@@ -195,6 +513,51 @@ fn set_run_in_background(state: tauri::State<AppState>, enabled: bool) -> Result
     Ok(())
 }
 
+fn launch_at_login_path(app: &tauri::AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(|dir| dir.join("launch-at-login.json"))
+}
+
+/// Register/unregister the app as a login item for the current platform
+/// and persist the preference so `get_launch_at_login` (and the
+/// re-apply-on-startup in `setup`) can read it back.
+#[tauri::command]
+fn set_launch_at_login(app: tauri::AppHandle, state: tauri::State<AppState>, enabled: bool) -> Result<(), String> {
+    let binary_path = std::env::current_exe()
+        .map_err(|e| format!("failed to resolve current executable: {e}"))?
+        .to_string_lossy()
+        .to_string();
+
+    if enabled {
+        launch_at_login::set_enabled(&binary_path, true)?;
+    } else {
+        launch_at_login::unset_enabled()?;
+    }
+
+    if let Some(path) = launch_at_login_path(&app) {
+        ensure_parent_dir(&path)?;
+        fs::write(&path, json!({ "enabled": enabled }).to_string())
+            .map_err(|e| format!("failed to persist launch-at-login preference: {e}"))?;
+    }
+
+    *state
+        .launch_at_login
+        .lock()
+        .map_err(|_| "Failed to update launch-at-login setting".to_string())? = enabled;
+    Ok(())
+}
+
+#[tauri::command]
+fn get_launch_at_login(state: tauri::State<AppState>) -> Result<bool, String> {
+    state
+        .launch_at_login
+        .lock()
+        .map(|guard| *guard)
+        .map_err(|_| "Failed to read launch-at-login setting".to_string())
+}
+
 #[tauri::command]
 fn set_mcp_settings(app: tauri::AppHandle, contents: String) -> Result<(), String> {
     let Some(path) = mcp_settings_path(&app) else {
@@ -205,6 +568,35 @@ fn set_mcp_settings(app: tauri::AppHandle, contents: String) -> Result<(), Strin
     Ok(())
 }
 
+#[tauri::command]
+fn create_capability(
+    app: tauri::AppHandle,
+    name: String,
+    agent_name: Option<String>,
+    permissions: Vec<String>,
+) -> Result<capabilities::Capability, String> {
+    let path = mcp_settings_path(&app).ok_or_else(|| "Failed to resolve MCP settings path.".to_string())?;
+    capabilities::create_capability(&path, name, agent_name, permissions)
+}
+
+#[tauri::command]
+fn add_permission(app: tauri::AppHandle, capability_id: String, permission: String) -> Result<(), String> {
+    let path = mcp_settings_path(&app).ok_or_else(|| "Failed to resolve MCP settings path.".to_string())?;
+    capabilities::add_permission(&path, &capability_id, permission)
+}
+
+#[tauri::command]
+fn remove_permission(app: tauri::AppHandle, capability_id: String, permission: String) -> Result<(), String> {
+    let path = mcp_settings_path(&app).ok_or_else(|| "Failed to resolve MCP settings path.".to_string())?;
+    capabilities::remove_permission(&path, &capability_id, &permission)
+}
+
+#[tauri::command]
+fn list_permissions(app: tauri::AppHandle, capability_id: String) -> Result<Vec<String>, String> {
+    let path = mcp_settings_path(&app).ok_or_else(|| "Failed to resolve MCP settings path.".to_string())?;
+    capabilities::list_permissions(&path, &capability_id)
+}
+
 fn read_mcp_settings(path: &Path) -> Value {
     let contents = fs::read_to_string(path).unwrap_or_else(|_| "{}".to_string());
     serde_json::from_str(&contents).unwrap_or_else(|_| json!({}))
@@ -242,7 +634,32 @@ fn build_mcp_tools(settings: &Value) -> Vec<Value> {
     list
 }
 
-fn start_mcp_server(app: &tauri::AppHandle) {
+/// Read the `"allowedOrigins"` array out of `mcp-settings.json`; an empty
+/// (or missing) list means no cross-origin browser request is allowed,
+/// only same-process callers that send no `Origin` header at all.
+fn allowed_origins(settings: &Value) -> Vec<String> {
+    settings
+        .get("allowedOrigins")
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+/// Send a JSON body with the standard `Content-Type` plus whatever
+/// hardening/CORS headers `mcp_security::security_headers` computed for
+/// this request.
+fn respond_json(request: Request, status: StatusCode, body: &Value, security: Vec<Header>) {
+    let mut response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(Header::from_bytes("Content-Type", "application/json").unwrap())
+        .with_header(Header::from_bytes("Cache-Control", "no-store").unwrap());
+    for header in security {
+        response = response.with_header(header);
+    }
+    let _ = request.respond(response);
+}
+
+fn start_mcp_server(app: &tauri::AppHandle, events: Arc<EventBus>, tokens: Arc<mcp_security::TokenStore>, db: Arc<Database>) {
     let Some(settings_path) = mcp_settings_path(app) else {
         return;
     };
@@ -255,29 +672,201 @@ fn start_mcp_server(app: &tauri::AppHandle) {
 
         for request in server.incoming_requests() {
             let url = request.url().to_string();
+            let path = url.split('?').next().unwrap_or(&url).to_string();
+            let query = parse_query(&url);
             let method = request.method().clone();
             let settings = read_mcp_settings(&settings_path);
-            let tools = build_mcp_tools(&settings);
+            let security = mcp_security::security_headers(&request, &allowed_origins(&settings));
+
+            if method == Method::Get && path == "/events/stream" {
+                let filter = EventFilter::from_query(
+                    query.get("agent_id").cloned(),
+                    query.get("task_type").cloned(),
+                );
+                events::serve_sse(request, &events, filter, security);
+                continue;
+            }
+
+            if method == Method::Get && path == "/events/ws" {
+                let filter = EventFilter::from_query(
+                    query.get("agent_id").cloned(),
+                    query.get("task_type").cloned(),
+                );
+                events::serve_websocket(request, &events, filter);
+                continue;
+            }
+
+            if method == Method::Post && path == "/mcp/call" {
+                let Some(claims) = tokens.claims_for_request(&request) else {
+                    respond_json(request, StatusCode(401), &json!({ "error": { "code": "unauthorized", "message": "missing or invalid bearer token" } }), security);
+                    continue;
+                };
+
+                let mut raw_body = String::new();
+                let _ = request.as_reader().read_to_string(&mut raw_body);
+                let tools = build_mcp_tools(&settings);
+                let enabled_tools: Vec<String> = tools
+                    .iter()
+                    .filter_map(|t| t.get("name").and_then(|n| n.as_str()).map(str::to_string))
+                    .collect();
+                let brain_root = settings.get("brainPath").and_then(|v| v.as_str()).map(PathBuf::from);
+
+                let (status, response_body) = match serde_json::from_str::<Value>(&raw_body) {
+                    Ok(call) => {
+                        let tool_name = call.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                        let arguments = call.get("arguments").cloned().unwrap_or_else(|| json!({}));
+                        let agent_name = arguments.get("agent_name").and_then(|v| v.as_str()).unwrap_or("default");
+                        let capability_config = capabilities::read_capabilities(&settings_path);
+                        let granted = capabilities::resolved_permissions(&capability_config, agent_name);
+                        let required = capabilities::required_permission(&tool_name, &arguments);
 
-            let (status, body) = match (method, url.as_str()) {
+                        if !claims.allows(&tool_name) {
+                            (StatusCode(403), json!({ "error": { "code": "forbidden", "message": format!("token is not scoped for tool '{tool_name}'") } }))
+                        } else if required.as_ref().map_or(false, |perm| !granted.contains(perm)) {
+                            (StatusCode(403), json!({ "error": { "code": "permission_denied", "message": format!("missing permission '{}'", required.unwrap()) } }))
+                        } else {
+                            match brain_root {
+                                Some(brain_root) => match mcp::dispatch(&tool_name, &arguments, &enabled_tools, &brain_root) {
+                                    Ok(result) => {
+                                        let audit = AuditEntry {
+                                            action: format!("mcp_call:{tool_name}"),
+                                            detail: arguments.to_string(),
+                                            timestamp: chrono::Utc::now().to_rfc3339(),
+                                            conversation_id: String::new(),
+                                        };
+                                        let _ = write_audit_entry(&brain_root, agent_name, &audit);
+                                        (StatusCode(200), json!({ "result": result }))
+                                    }
+                                    Err(err) => (StatusCode(400), err.to_json()),
+                                },
+                                None => (
+                                    StatusCode(500),
+                                    json!({ "error": { "code": "no_brain_path", "message": "No brain folder configured in mcp-settings.json" } }),
+                                ),
+                            }
+                        }
+                    }
+                    Err(e) => (
+                        StatusCode(400),
+                        json!({ "error": { "code": "invalid_body", "message": format!("invalid JSON body: {e}") } }),
+                    ),
+                };
+
+                respond_json(request, status, &response_body, security);
+                continue;
+            }
+
+            if method == Method::Get && path == "/mcp/tools" {
+                let Some(claims) = tokens.claims_for_request(&request) else {
+                    respond_json(request, StatusCode(401), &json!({ "error": { "code": "unauthorized", "message": "missing or invalid bearer token" } }), security);
+                    continue;
+                };
+                let tools: Vec<Value> = build_mcp_tools(&settings)
+                    .into_iter()
+                    .filter(|tool| tool.get("name").and_then(|n| n.as_str()).map_or(false, |n| claims.allows(n)))
+                    .collect();
+                let agent_name = query.get("agent_name").map(String::as_str).unwrap_or("default");
+                let capability_config = capabilities::read_capabilities(&settings_path);
+                let permissions = capabilities::resolved_permissions(&capability_config, agent_name);
+                respond_json(request, StatusCode(200), &json!({ "tools": tools, "permissions": permissions }), security);
+                continue;
+            }
+
+            if method == Method::Post && path.starts_with("/webhooks/") {
+                let trigger_id = path["/webhooks/".len()..].to_string();
+
+                let trigger = match db.get_triggers(None) {
+                    Ok(triggers) => triggers.into_iter().find(|t| t.id == trigger_id && t.trigger_type == "webhook"),
+                    Err(e) => {
+                        respond_json(request, StatusCode(500), &json!({ "error": { "code": "internal_error", "message": format!("failed to load triggers: {e}") } }), security);
+                        continue;
+                    }
+                };
+                let Some(trigger) = trigger else {
+                    respond_json(request, StatusCode(404), &json!({ "error": { "code": "not_found", "message": "no webhook trigger with that id" } }), security);
+                    continue;
+                };
+                let webhook_config: triggers::WebhookTriggerConfig = match serde_json::from_value(trigger.config.clone()) {
+                    Ok(config) => config,
+                    Err(e) => {
+                        respond_json(request, StatusCode(500), &json!({ "error": { "code": "invalid_config", "message": format!("malformed webhook trigger config: {e}") } }), security);
+                        continue;
+                    }
+                };
+
+                let signature = request
+                    .headers()
+                    .iter()
+                    .find(|h| h.field.as_str().as_str().eq_ignore_ascii_case(webhook_config.signature_header_name()))
+                    .map(|h| h.value.as_str().to_string());
+                let mut raw_body = Vec::new();
+                let _ = request.as_reader().read_to_end(&mut raw_body);
+
+                match triggers::handle_webhook(&events, &trigger, &webhook_config, "POST", &raw_body, signature.as_deref()) {
+                    Ok(_event) => {
+                        if trigger.enabled {
+                            if let Err(e) = db.create_task(&trigger.agent_id, &trigger.task_type, trigger.task_input.clone(), None) {
+                                eprintln!("Failed to dispatch task for webhook trigger '{}': {e}", trigger.name);
+                            }
+                        }
+                        respond_json(request, StatusCode(200), &json!({ "ok": true }), security);
+                    }
+                    Err((err, entry)) => {
+                        let _ = db.log_activity(entry.agent_id.as_deref(), entry.task_id.as_deref(), &entry.action, entry.details.as_deref());
+                        let status = match err {
+                            triggers::WebhookVerificationError::MethodMismatch { .. } => StatusCode(405),
+                            _ => StatusCode(401),
+                        };
+                        respond_json(request, status, &json!({ "error": { "code": "webhook_verification_failed", "message": err.to_string() } }), security);
+                    }
+                }
+                continue;
+            }
+
+            if method == Method::Get && path == "/approvals.atom" {
+                let status = query.get("status").map(String::as_str);
+                let body = match db.list_approvals_by_status(status) {
+                    Ok(approvals) => feeds::render_approval_feed(&approvals, &format!("http://127.0.0.1:8787{url}")),
+                    Err(e) => {
+                        respond_json(request, StatusCode(500), &json!({ "error": { "code": "internal_error", "message": format!("failed to load approvals: {e}") } }), security);
+                        continue;
+                    }
+                };
+                let mut response = Response::from_string(body)
+                    .with_header(Header::from_bytes("Content-Type", "application/atom+xml; charset=utf-8").unwrap())
+                    .with_header(Header::from_bytes("Cache-Control", "max-age=60").unwrap());
+                for header in security {
+                    response = response.with_header(header);
+                }
+                let _ = request.respond(response);
+                continue;
+            }
+
+            if method == Method::Get && path == "/schedules.ics" {
+                let now = chrono::Utc::now().to_rfc3339();
+                let agent_id = query.get("agent_id").map(String::as_str);
+                let body = match db.get_schedules(agent_id) {
+                    Ok(schedules) => ical_export::render_calendar(&schedules, &now),
+                    Err(e) => {
+                        eprintln!("Failed to load schedules for /schedules.ics, falling back to templates: {e}");
+                        ical_export::render_template_calendar(&scheduler::get_schedule_templates(), &now)
+                    }
+                };
+                let mut response = Response::from_string(body)
+                    .with_header(Header::from_bytes("Content-Type", "text/calendar; charset=utf-8").unwrap())
+                    .with_header(Header::from_bytes("Cache-Control", "max-age=900").unwrap());
+                for header in security {
+                    response = response.with_header(header);
+                }
+                let _ = request.respond(response);
+                continue;
+            }
+
+            let (status, body) = match (method, path.as_str()) {
                 (Method::Get, "/mcp/health") => (StatusCode(200), json!({ "ok": true })),
-                (Method::Get, "/mcp/tools") => (StatusCode(200), json!({ "tools": tools })),
-                (Method::Post, "/mcp/call") => (
-                    StatusCode(501),
-                    json!({ "error": "MCP call not implemented yet." }),
-                ),
                 _ => (StatusCode(404), json!({ "error": "Not found" })),
             };
-
-            let response = Response::from_string(body.to_string())
-                .with_status_code(status)
-                .with_header(
-                    Header::from_bytes("Content-Type", "application/json").unwrap(),
-                )
-                .with_header(
-                    Header::from_bytes("Access-Control-Allow-Origin", "*").unwrap(),
-                );
-            let _ = request.respond(response);
+            respond_json(request, status, &body, security);
         }
     });
 }
@@ -289,25 +878,8 @@ fn append_memory_entry(
 ) -> Result<(), String> {
     let entry: MemoryEntry =
         serde_json::from_str(&entry).map_err(|e| format!("Invalid memory entry: {e}"))?;
-    if brain_path.trim().is_empty() {
-        return Err("Brain folder path is missing.".to_string());
-    }
-    let safe_agent = sanitize_agent_name(&agent_name);
-    let base = PathBuf::from(brain_path);
-    let target = base
-        .join("AgentForge Brain")
-        .join(safe_agent)
-        .join("memory");
-    fs::create_dir_all(&target).map_err(|e| format!("Failed to create memory folder: {e}"))?;
-    let file_path = target.join("memory.jsonl");
-    let mut file = fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&file_path)
-        .map_err(|e| format!("Failed to open memory file: {e}"))?;
-    let line = serde_json::to_string(&entry).map_err(|e| format!("Failed to serialize entry: {e}"))?;
-    writeln!(file, "{line}").map_err(|e| format!("Failed to write memory entry: {e}"))?;
-    Ok(())
+    let store = open_brain_store(&brain_path)?;
+    store.append_memory(&agent_name, &entry)
 }
 
 #[tauri::command]
@@ -317,55 +889,17 @@ fn query_memory_entries(
     query: String,
     limit: usize,
 ) -> Result<Vec<MemoryMatch>, String> {
-    if brain_path.trim().is_empty() {
-        return Err("Brain folder path is missing.".to_string());
-    }
-    let safe_agent = sanitize_agent_name(&agent_name);
-    let base = PathBuf::from(brain_path);
-    let file_path = base
-        .join("AgentForge Brain")
-        .join(safe_agent)
-        .join("memory")
-        .join("memory.jsonl");
-
-    let contents = fs::read_to_string(&file_path).unwrap_or_default();
-    let tokens: Vec<String> = query
-        .to_lowercase()
-        .split_whitespace()
-        .filter(|token| token.len() > 2)
-        .map(|token| token.to_string())
-        .collect();
-
-    if tokens.is_empty() {
-        return Ok(vec![]);
-    }
-
-    let mut scored: Vec<(i32, MemoryEntry)> = Vec::new();
-    for line in contents.lines() {
-        let Ok(entry) = serde_json::from_str::<MemoryEntry>(line) else { continue };
-        let haystack = entry.content.to_lowercase();
-        let mut score = 0;
-        for token in &tokens {
-            if haystack.contains(token) {
-                score += 1;
-            }
-        }
-        if score > 0 {
-            scored.push((score, entry));
-        }
-    }
+    let store = open_brain_store(&brain_path)?;
+    store.query_memory(&agent_name, &query, limit)
+}
 
-    scored.sort_by(|a, b| b.0.cmp(&a.0));
-    let results = scored
-        .into_iter()
-        .take(limit.max(1))
-        .map(|(_, entry)| MemoryMatch {
-            content: entry.content,
-            role: entry.role,
-            timestamp: entry.timestamp,
-        })
-        .collect::<Vec<_>>();
-    Ok(results)
+/// Append an `AuditEntry` to the brain store rooted at `brain_root`.
+/// Shared by the `append_audit_entry` command and the MCP tool dispatcher
+/// so every tool invocation leaves the same traceable record.
+fn write_audit_entry(brain_root: &Path, agent_name: &str, entry: &AuditEntry) -> Result<(), String> {
+    let backend = brain_backend_setting(brain_root);
+    let store = build_brain_store(brain_root.to_path_buf(), backend.as_deref())?;
+    store.append_audit(agent_name, entry)
 }
 
 #[tauri::command]
@@ -379,24 +913,246 @@ fn append_audit_entry(
     if brain_path.trim().is_empty() {
         return Err("Brain folder path is missing.".to_string());
     }
-    let safe_agent = sanitize_agent_name(&agent_name);
-    let base = PathBuf::from(brain_path);
-    let target = base
-        .join("AgentForge Brain")
-        .join(safe_agent)
-        .join("audit");
-    fs::create_dir_all(&target).map_err(|e| format!("Failed to create audit folder: {e}"))?;
-    let file_path = target.join("audit.jsonl");
-    let mut file = fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&file_path)
-        .map_err(|e| format!("Failed to open audit file: {e}"))?;
-    let line = serde_json::to_string(&entry).map_err(|e| format!("Failed to serialize entry: {e}"))?;
-    writeln!(file, "{line}").map_err(|e| format!("Failed to write audit entry: {e}"))?;
+    write_audit_entry(&PathBuf::from(brain_path), &agent_name, &entry)
+}
+
+// ============================================================================
+// Database-backed commands (agents, tasks, schedules, triggers, activity,
+// approvals) — thin wrappers over `Store`, mirroring the
+// JSON-string-argument convention the brain-store commands above already
+// use for anything the frontend only has as a raw string (e.g. `Value`
+// payloads arrive pre-parsed by Tauri's own IPC, so no extra
+// `serde_json::from_str` is needed here).
+// ============================================================================
+
+#[tauri::command]
+fn save_agent(state: tauri::State<AppState>, config: AgentConfig) -> Result<String, String> {
+    state.db.save_agent(&config).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_agents(state: tauri::State<AppState>) -> Result<Vec<AgentConfig>, String> {
+    state.db.get_agents().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_agent(state: tauri::State<AppState>, agent_id: String) -> Result<(), String> {
+    state.db.delete_agent(&agent_id).map_err(|e| e.to_string())
+}
+
+/// Persist `task` to the crash-recovery spool, logging (rather than
+/// failing the command on) a spool write error — `db` already has the
+/// durable record; the spool is a recovery aid, not the source of truth.
+fn mirror_to_spool(state: &tauri::State<AppState>, task: &tasks::Task) {
+    if let Err(e) = state.spool.save(task.clone()) {
+        eprintln!("Failed to spool task '{}': {e}", task.id);
+    }
+}
+
+#[tauri::command]
+fn create_task(
+    state: tauri::State<AppState>,
+    agent_id: String,
+    task_type: String,
+    input: Value,
+    scheduled_at: Option<String>,
+) -> Result<tasks::Task, String> {
+    let task = state.db.create_task(&agent_id, &task_type, input, scheduled_at).map_err(|e| e.to_string())?;
+    mirror_to_spool(&state, &task);
+    Ok(task)
+}
+
+#[tauri::command]
+fn get_tasks(state: tauri::State<AppState>, agent_id: Option<String>, status: Option<String>) -> Result<Vec<tasks::Task>, String> {
+    state.db.get_tasks(agent_id.as_deref(), status.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Claim the next task, same as `Store::claim_next_task`, but subject to
+/// the claiming agent's `throttle` quota: a task that would exceed its
+/// agent's concurrency/rate limit is put back to `pending` and the
+/// command reports no task available, rather than handing out a task the
+/// caller shouldn't start running yet.
+#[tauri::command]
+fn claim_next_task(state: tauri::State<AppState>, agent_id: Option<String>) -> Result<Option<tasks::Task>, String> {
+    let Some(task) = state.db.claim_next_task(agent_id.as_deref()).map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+
+    if let throttle::ThrottleDecision::Hold { reason, .. } = state.throttle.check(&task.agent_id, &task.task_type) {
+        state.db.revert_claim(&task.id).map_err(|e| e.to_string())?;
+        let entry = throttle::throttled_activity_entry(&task.agent_id, &task.id, &reason);
+        let _ = state.db.log_activity(entry.agent_id.as_deref(), entry.task_id.as_deref(), &entry.action, entry.details.as_deref());
+        return Ok(None);
+    }
+
+    mirror_to_spool(&state, &task);
+    Ok(Some(task))
+}
+
+/// Update a task's status, same as `Store::update_task_status`, except a
+/// `"failed"` status is routed through `Store::fail_task` instead: it
+/// decides whether the failure retries (with backoff) or dead-letters,
+/// based on `task_error` (a structured `TaskError`, e.g. `{"kind":
+/// "tool_failure", "tool": "...", "detail": "...", "transient": true}`)
+/// when the caller has one, falling back to treating `error` as an
+/// irrecoverable, non-retryable failure when it doesn't.
+#[tauri::command]
+fn update_task_status(
+    state: tauri::State<AppState>,
+    task_id: String,
+    status: String,
+    result: Option<Value>,
+    error: Option<String>,
+    task_error: Option<Value>,
+) -> Result<(), String> {
+    if status == "failed" {
+        let parsed = errors::TaskError::from_value_or_string(task_error.as_ref(), error.as_deref())
+            .unwrap_or(errors::TaskError::Irrecoverable { detail: "task failed".to_string() });
+        state
+            .db
+            .fail_task(&task_id, parsed, &tasks::TaskExecutionConfig::default())
+            .map_err(|e| e.to_string())?;
+    } else {
+        state.db.update_task_status(&task_id, &status, result, error).map_err(|e| e.to_string())?;
+    }
+
+    if let Some(task) = state.db.get_task(&task_id).map_err(|e| e.to_string())? {
+        // A failure leaves `running` whether it dead-lettered or was
+        // rescheduled for a retry.
+        if status == "failed" || matches!(task.status.as_str(), "completed" | "failed" | "cancelled") {
+            state.throttle.release(&task.agent_id);
+        }
+        mirror_to_spool(&state, &task);
+    }
+    Ok(())
+}
+
+/// Every `DeadLetterReport` produced so far by `fail_task` exhausting a
+/// task's retries.
+#[tauri::command]
+fn get_dead_letters(state: tauri::State<AppState>) -> Vec<backoff::DeadLetterReport> {
+    state.dead_letter_sink.reports()
+}
+
+/// Status counts over every task held in the on-disk spool: those
+/// recovered at startup plus every task `mirror_to_spool` has saved since.
+/// The UI/ops surface can compare these with `db` to reconcile rather than
+/// silently trusting `db` alone after an unclean shutdown.
+#[tauri::command]
+fn get_spooled_task_stats(state: tauri::State<AppState>) -> tasks::TaskStats {
+    state.spool.stats()
+}
+
+#[tauri::command]
+fn create_schedule(
+    state: tauri::State<AppState>,
+    agent_id: String,
+    name: String,
+    cron_expr: Option<String>,
+    run_at: Option<String>,
+    task_type: String,
+    task_input: Value,
+    timezone: Option<String>,
+    catch_up_missed: bool,
+) -> Result<scheduler::Schedule, String> {
+    let schedule = state
+        .db
+        .create_schedule(&agent_id, &name, cron_expr.as_deref(), run_at.as_deref(), &task_type, task_input, timezone.as_deref(), catch_up_missed)
+        .map_err(|e| e.to_string())?;
+    sync_schedule_os(&schedule);
+    Ok(schedule)
+}
+
+#[tauri::command]
+fn get_schedules(state: tauri::State<AppState>, agent_id: Option<String>) -> Result<Vec<scheduler::Schedule>, String> {
+    state.db.get_schedules(agent_id.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_schedule(state: tauri::State<AppState>, schedule_id: String) -> Result<(), String> {
+    state.db.delete_schedule(&schedule_id).map_err(|e| e.to_string())?;
+    if let Err(e) = os_scheduler::uninstall(&schedule_id) {
+        eprintln!("Failed to uninstall native scheduler entry for '{schedule_id}': {e}");
+    }
     Ok(())
 }
 
+#[tauri::command]
+fn toggle_schedule(state: tauri::State<AppState>, schedule_id: String, enabled: bool) -> Result<(), String> {
+    state.db.toggle_schedule(&schedule_id, enabled).map_err(|e| e.to_string())?;
+    if let Some(schedule) = state.db.get_schedules(None).map_err(|e| e.to_string())?.into_iter().find(|s| s.id == schedule_id) {
+        sync_schedule_os(&schedule);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn create_trigger(
+    state: tauri::State<AppState>,
+    agent_id: String,
+    name: String,
+    trigger_type: String,
+    config: Value,
+    task_type: String,
+    task_input: Value,
+) -> Result<triggers::Trigger, String> {
+    state.db.create_trigger(&agent_id, &name, &trigger_type, config, &task_type, task_input).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_triggers(state: tauri::State<AppState>, agent_id: Option<String>) -> Result<Vec<triggers::Trigger>, String> {
+    state.db.get_triggers(agent_id.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_trigger(state: tauri::State<AppState>, trigger_id: String) -> Result<(), String> {
+    state.db.delete_trigger(&trigger_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_activity(state: tauri::State<AppState>, agent_id: Option<String>, limit: u32) -> Result<Vec<tasks::ActivityLogEntry>, String> {
+    state.db.get_activity_log(agent_id.as_deref(), limit).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_task_stats(state: tauri::State<AppState>, agent_id: Option<String>) -> Result<tasks::TaskStats, String> {
+    state.db.get_task_stats(agent_id.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn create_approval_request(
+    state: tauri::State<AppState>,
+    agent_id: String,
+    task_id: Option<String>,
+    action_type: String,
+    action_details: Value,
+    risk_level: String,
+) -> Result<tasks::ApprovalRequest, String> {
+    state
+        .db
+        .create_approval_request(&agent_id, task_id.as_deref(), &action_type, action_details, &risk_level)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn list_approvals(state: tauri::State<AppState>, status: Option<String>) -> Result<Vec<tasks::ApprovalRequest>, String> {
+    state.db.list_approvals_by_status(status.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn process_approval(
+    state: tauri::State<AppState>,
+    approval_id: String,
+    approved: bool,
+    modified_input: Option<Value>,
+    expected_status: String,
+) -> Result<(), String> {
+    state
+        .db
+        .process_approval(&approval_id, approved, modified_input, &expected_status)
+        .map_err(|e| e.to_string())
+}
+
 fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
@@ -411,7 +1167,42 @@ fn main() {
             set_secret,
             get_secret,
             delete_secret,
-            set_run_in_background
+            set_run_in_background,
+            set_launch_at_login,
+            get_launch_at_login,
+            get_backend_info,
+            mint_mcp_token,
+            create_capability,
+            add_permission,
+            remove_permission,
+            list_permissions,
+            save_agent,
+            get_agents,
+            delete_agent,
+            create_task,
+            get_tasks,
+            claim_next_task,
+            update_task_status,
+            create_schedule,
+            get_schedules,
+            delete_schedule,
+            toggle_schedule,
+            create_trigger,
+            get_triggers,
+            delete_trigger,
+            get_activity,
+            get_task_stats,
+            get_spooled_task_stats,
+            get_dead_letters,
+            create_approval_request,
+            list_approvals,
+            process_approval,
+            list_credential_types,
+            register_credential_type,
+            validate_credential,
+            store_tool_credential,
+            get_tool_credential,
+            delete_tool_credential
         ])
         .setup(|app| {
             // Handle deep links (agentforge:// URLs)
@@ -421,15 +1212,83 @@ fn main() {
                 app.deep_link().register_all()?;
             }
 
+            let events = Arc::new(EventBus::new());
+
+            // Mint a fresh root MCP bearer token on every launch and stash
+            // it in the keychain so the UI can read it back the same way
+            // it reads any other secret.
+            let mcp_tokens = Arc::new(mcp_security::TokenStore::new());
+            let root_token = mcp_tokens.mint(vec!["*".to_string()]);
+            let _ = credentials::store_credential(KEYCHAIN_SERVICE, "mcp-root-token", &root_token);
+
+            let app_handle = app.handle();
+
+            // Re-apply the persisted launch-at-login preference (the
+            // native login item itself, e.g. a LaunchAgent, is reasserted
+            // on every launch rather than only when the toggle changes,
+            // so a reinstall or binary move doesn't silently break it).
+            let launch_at_login_enabled = launch_at_login_path(&app_handle)
+                .and_then(|path| fs::read_to_string(path).ok())
+                .and_then(|contents| serde_json::from_str::<Value>(&contents).ok())
+                .and_then(|settings| settings.get("enabled").and_then(Value::as_bool).or(Some(false)))
+                .unwrap_or(false);
+            if launch_at_login_enabled {
+                if let Ok(binary_path) = std::env::current_exe() {
+                    let _ = launch_at_login::set_enabled(&binary_path.to_string_lossy(), true);
+                }
+            }
+
+            let dead_letter_sink = Arc::new(backoff::InMemoryDeadLetterSink::new());
+            let policy = load_policy(&app_handle);
+            let settings = mcp_settings_path(&app_handle).as_deref().map(read_mcp_settings).unwrap_or_else(|| json!({}));
+            let notifier = build_notifier(&app_handle, &settings);
+
+            let db_path = database_path(&app_handle)
+                .ok_or_else(|| "Failed to resolve app data directory for the database.".to_string())?;
+            ensure_parent_dir(&db_path)?;
+            let db = Arc::new(
+                Database::new(&db_path, events.clone(), notifier, Some(policy), Some(dead_letter_sink.clone() as Arc<dyn backoff::DeadLetterSink>))
+                    .map_err(|e| format!("Failed to open database: {e}"))?,
+            );
+
+            let spool_path = spool_dir(&app_handle)
+                .ok_or_else(|| "Failed to resolve app data directory for the task spool.".to_string())?;
+            let spool = Arc::new(
+                spool::TaskSpool::open(&spool_path).map_err(|e| format!("Failed to open task spool: {e}"))?,
+            );
+
+            start_schedule_loop(db.clone(), spool.clone());
+
+            let db_for_server = db.clone();
+            let throttle = Arc::new(throttle::Throttle::new());
+
             app.manage(AppState {
                 run_in_background: Mutex::new(false),
+                launch_at_login: Mutex::new(launch_at_login_enabled),
+                events: events.clone(),
+                mcp_tokens: mcp_tokens.clone(),
+                db,
+                spool,
+                throttle,
+                dead_letter_sink,
+                #[cfg(feature = "embedded_python")]
+                agent_server: Mutex::new(None),
             });
 
-            let app_handle = app.handle();
+            // `--minimized`/headless: come up tray-resident rather than
+            // with a visible window, e.g. when launched as a login item.
+            if std::env::args().any(|arg| arg == "--minimized") {
+                if let Some(window) = app_handle.get_webview_window("main") {
+                    let _ = window.hide();
+                }
+            }
 
             // Start the local Python backend
             spawn_backend(&app_handle);
-            start_mcp_server(&app_handle);
+            start_mcp_server(&app_handle, events, mcp_tokens, db_for_server);
+            if let Some(settings_path) = mcp_settings_path(&app_handle) {
+                memory_tail::start_memory_tail(&app_handle, settings_path);
+            }
 
             // Handle file-open at app launch (e.g., double-click .agentforge file)
             if let Some(path) = std::env::args().skip(1).find(|arg| arg.ends_with(".agentforge"))
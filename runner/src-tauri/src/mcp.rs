@@ -0,0 +1,148 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde_json::{json, Value};
+
+/// A structured MCP tool-call failure, shaped to serialize as
+/// `{"error": {"code": ..., "message": ...}}`.
+#[derive(Debug)]
+pub struct ToolError {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl ToolError {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({ "error": { "code": self.code, "message": self.message } })
+    }
+}
+
+/// Dispatch a `{"name": ..., "arguments": {...}}` MCP call to the matching
+/// per-tool handler, after checking the tool is enabled.
+pub fn dispatch(tool_name: &str, arguments: &Value, enabled_tools: &[String], brain_root: &Path) -> Result<Value, ToolError> {
+    if !enabled_tools.iter().any(|t| t == tool_name) {
+        return Err(ToolError::new("tool_disabled", format!("Tool '{tool_name}' is not enabled")));
+    }
+
+    match tool_name {
+        "local_files" => local_files::dispatch(arguments, brain_root),
+        "terminal" => terminal::dispatch(arguments),
+        "google_drive" | "browser" => Err(ToolError::new(
+            "not_implemented",
+            format!("Tool '{tool_name}' has no handler yet"),
+        )),
+        _ => Err(ToolError::new("unknown_tool", format!("Unknown tool '{tool_name}'"))),
+    }
+}
+
+/// Resolve `relative` against `base` and make sure the result stays inside
+/// `base`, rejecting any `..`/absolute-path escape attempt.
+fn jail_path(base: &Path, relative: &str) -> Result<PathBuf, ToolError> {
+    let candidate = base.join(relative);
+    let normalized = normalize(&candidate);
+    let normalized_base = normalize(base);
+    if !normalized.starts_with(&normalized_base) {
+        return Err(ToolError::new("path_escape", format!("'{relative}' escapes the brain folder")));
+    }
+    Ok(normalized)
+}
+
+/// Lexically normalize a path (collapsing `.`/`..`) without requiring the
+/// path to exist, since `fs::canonicalize` fails on files we're about to
+/// create.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::ParentDir => {
+                out.pop();
+            }
+            std::path::Component::CurDir => {}
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+mod local_files {
+    use super::*;
+    use std::fs;
+
+    pub fn dispatch(arguments: &Value, brain_root: &Path) -> Result<Value, ToolError> {
+        let action = arguments
+            .get("action")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::new("bad_arguments", "missing 'action' (expected 'read' or 'write')"))?;
+        let path = arguments
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::new("bad_arguments", "missing 'path'"))?;
+
+        let target = jail_path(brain_root, path)?;
+
+        match action {
+            "read" => {
+                let contents = fs::read_to_string(&target)
+                    .map_err(|e| ToolError::new("io_error", format!("failed to read '{path}': {e}")))?;
+                Ok(json!({ "contents": contents }))
+            }
+            "write" => {
+                let contents = arguments
+                    .get("contents")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| ToolError::new("bad_arguments", "missing 'contents' for write"))?;
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| ToolError::new("io_error", format!("failed to create directory: {e}")))?;
+                }
+                fs::write(&target, contents)
+                    .map_err(|e| ToolError::new("io_error", format!("failed to write '{path}': {e}")))?;
+                Ok(json!({ "written_bytes": contents.len() }))
+            }
+            other => Err(ToolError::new("bad_arguments", format!("unknown action '{other}'"))),
+        }
+    }
+}
+
+mod terminal {
+    use super::*;
+
+    /// Commands an agent is allowed to run. Arguments are passed through,
+    /// but the executable itself must be one of these.
+    const ALLOWLIST: &[&str] = &["git", "ls", "cat", "pwd", "echo"];
+
+    pub fn dispatch(arguments: &Value) -> Result<Value, ToolError> {
+        let command = arguments
+            .get("command")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::new("bad_arguments", "missing 'command'"))?;
+
+        if !ALLOWLIST.contains(&command) {
+            return Err(ToolError::new(
+                "command_not_allowed",
+                format!("'{command}' is not on the terminal tool's allowlist"),
+            ));
+        }
+
+        let args: Vec<String> = arguments
+            .get("args")
+            .and_then(|v| v.as_array())
+            .map(|items| items.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+            .unwrap_or_default();
+
+        let output = Command::new(command)
+            .args(&args)
+            .output()
+            .map_err(|e| ToolError::new("exec_failed", format!("failed to run '{command}': {e}")))?;
+
+        Ok(json!({
+            "stdout": String::from_utf8_lossy(&output.stdout),
+            "stderr": String::from_utf8_lossy(&output.stderr),
+            "exit_code": output.status.code(),
+        }))
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use base64::Engine;
+use rand::RngCore;
+use tiny_http::{Header, Request};
+
+/// The claims carried by one minted bearer token: which tool names it may
+/// invoke. A scope of `"*"` grants every tool.
+#[derive(Clone)]
+pub struct TokenClaims {
+    scopes: Vec<String>,
+}
+
+impl TokenClaims {
+    pub fn allows(&self, tool_name: &str) -> bool {
+        self.scopes.iter().any(|s| s == "*" || s == tool_name)
+    }
+}
+
+/// In-memory registry of bearer tokens minted for the local MCP server.
+/// Tokens are opaque random strings scoped to a set of tool names; losing
+/// the registry (e.g. on app restart) simply invalidates previously issued
+/// tokens, which is fine since the root token is re-minted on every
+/// startup and persisted to the keychain for the UI to pick back up.
+#[derive(Default)]
+pub struct TokenStore {
+    tokens: Mutex<HashMap<String, TokenClaims>>,
+}
+
+impl TokenStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mint a new random bearer token scoped to `scopes` and register it.
+    pub fn mint(&self, scopes: Vec<String>) -> String {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let token = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes);
+        self.tokens.lock().unwrap().insert(token.clone(), TokenClaims { scopes });
+        token
+    }
+
+    fn claims(&self, token: &str) -> Option<TokenClaims> {
+        self.tokens.lock().unwrap().get(token).cloned()
+    }
+
+    /// Look up the claims for the request's `Authorization: Bearer <token>`
+    /// header, or `None` if it's missing, malformed, or names an unknown
+    /// token.
+    pub fn claims_for_request(&self, request: &Request) -> Option<TokenClaims> {
+        let token = bearer_token(request)?;
+        self.claims(&token)
+    }
+}
+
+fn bearer_token(request: &Request) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.as_str().as_str().eq_ignore_ascii_case("Authorization"))
+        .map(|h| h.value.as_str())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string)
+}
+
+/// Resolve the `Access-Control-Allow-Origin` value for a request's `Origin`
+/// header against a configured allowlist. With no `Origin` header (a
+/// same-process tool, not a browser) every request passes; a present
+/// `Origin` must match the allowlist exactly or the response carries no
+/// CORS header at all, denying the read cross-origin.
+fn cors_origin(request: &Request, allowed_origins: &[String]) -> Option<String> {
+    let origin = request
+        .headers()
+        .iter()
+        .find(|h| h.field.as_str().as_str().eq_ignore_ascii_case("Origin"))
+        .map(|h| h.value.as_str().to_string())?;
+    allowed_origins.iter().any(|allowed| allowed == &origin).then_some(origin)
+}
+
+/// Standard hardening headers (CORS, nosniff, CSP) applied to every
+/// response the MCP server sends except the WebSocket upgrade handshake
+/// (`events::serve_websocket`), which goes out without them, in place of the old blanket
+/// `Access-Control-Allow-Origin: *`. Callers that want caching disabled
+/// too (the JSON tool-call responses) add their own `Cache-Control`.
+pub fn security_headers(request: &Request, allowed_origins: &[String]) -> Vec<Header> {
+    let mut headers = vec![
+        Header::from_bytes("X-Content-Type-Options", "nosniff").unwrap(),
+        Header::from_bytes("Content-Security-Policy", "default-src 'none'").unwrap(),
+    ];
+    if let Some(origin) = cors_origin(request, allowed_origins) {
+        headers.push(Header::from_bytes("Access-Control-Allow-Origin", origin).unwrap());
+    }
+    headers
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use tauri::Emitter;
+
+use crate::brain_store::MemoryEntry;
+use crate::read_mcp_settings;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Byte offset we've already emitted for one `memory.jsonl`, plus any
+/// trailing partial line buffered until the rest of it arrives.
+#[derive(Default)]
+struct TailState {
+    offset: u64,
+    partial_line: String,
+}
+
+/// Spawn a background thread (mirroring `start_mcp_server`'s thread-per-loop
+/// style) that follows every agent's `memory.jsonl` under the configured
+/// brain folder and emits `agentforge://memory-appended` with each newly
+/// parsed `MemoryEntry` as it's written.
+pub fn start_memory_tail(app: &tauri::AppHandle, settings_path: PathBuf) {
+    let app = app.clone();
+    thread::spawn(move || {
+        let mut states: HashMap<PathBuf, TailState> = HashMap::new();
+        loop {
+            let settings = read_mcp_settings(&settings_path);
+            if let Some(brain_path) = settings.get("brainPath").and_then(|v| v.as_str()) {
+                for memory_file in discover_memory_files(Path::new(brain_path)) {
+                    let state = states.entry(memory_file.clone()).or_default();
+                    poll_file(&app, &memory_file, state);
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+/// Find every `<brain>/AgentForge Brain/<agent>/memory/memory.jsonl`.
+fn discover_memory_files(brain_root: &Path) -> Vec<PathBuf> {
+    let agents_dir = brain_root.join("AgentForge Brain");
+    let Ok(entries) = std::fs::read_dir(&agents_dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join("memory").join("memory.jsonl"))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Read any bytes appended to `path` since `state.offset`, split them on
+/// newlines (carrying a trailing partial line over to the next poll), and
+/// emit a `MemoryEntry` for each complete line that parses. Truncation or
+/// rotation (the file got shorter than our offset) resets the cursor to
+/// the start of the file.
+fn poll_file(app: &tauri::AppHandle, path: &Path, state: &mut TailState) {
+    let Ok(mut file) = File::open(path) else {
+        return;
+    };
+    let Ok(metadata) = file.metadata() else {
+        return;
+    };
+    let len = metadata.len();
+
+    if len < state.offset {
+        state.offset = 0;
+        state.partial_line.clear();
+    }
+    if len == state.offset {
+        return;
+    }
+
+    if file.seek(SeekFrom::Start(state.offset)).is_err() {
+        return;
+    }
+
+    let mut chunk = String::new();
+    if file.read_to_string(&mut chunk).is_err() {
+        return;
+    }
+    state.offset = len;
+
+    state.partial_line.push_str(&chunk);
+    let mut lines: Vec<String> = state.partial_line.split('\n').map(str::to_string).collect();
+    state.partial_line = lines.pop().unwrap_or_default();
+
+    for line in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Ok(entry) = serde_json::from_str::<MemoryEntry>(&line) {
+            let _ = app.emit("agentforge://memory-appended", &entry);
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::tasks::{ActivityLogEntry, ApprovalRequest};
+
+/// A channel an approval notification can be dispatched through.
+pub trait NotificationChannel: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn send(&self, message: &str) -> Result<(), String>;
+}
+
+/// Emits the message as an `agentforge://approval-notification` Tauri event
+/// to the app's own windows; no OS-level notification is raised here.
+pub struct DesktopChannel {
+    pub app: tauri::AppHandle,
+}
+
+impl NotificationChannel for DesktopChannel {
+    fn name(&self) -> &'static str {
+        "desktop"
+    }
+
+    fn send(&self, message: &str) -> Result<(), String> {
+        use tauri::Emitter;
+        self.app
+            .emit("agentforge://approval-notification", message)
+            .map_err(|e| format!("failed to emit desktop notification: {e}"))
+    }
+}
+
+/// Sends an email notification through a transactional-email HTTP API.
+pub struct EmailChannel {
+    pub send_url: String,
+    pub to: String,
+}
+
+impl NotificationChannel for EmailChannel {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    fn send(&self, message: &str) -> Result<(), String> {
+        ureq::post(&self.send_url)
+            .send_json(serde_json::json!({ "to": self.to, "subject": "AgentForge approval needed", "body": message }))
+            .map(|_| ())
+            .map_err(|e| format!("email notification failed: {e}"))
+    }
+}
+
+/// POSTs the notification to an outbound webhook (Slack/Discord/generic).
+pub struct WebhookChannel {
+    pub url: String,
+}
+
+impl NotificationChannel for WebhookChannel {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn send(&self, message: &str) -> Result<(), String> {
+        ureq::post(&self.url)
+            .send_json(serde_json::json!({ "text": message }))
+            .map(|_| ())
+            .map_err(|e| format!("webhook notification failed: {e}"))
+    }
+}
+
+pub enum NotifyOutcome {
+    Sent,
+    Suppressed,
+}
+
+/// A lifecycle moment in an approval request's life, passed to
+/// `Notifier::notify` so every caller along the request/approve/reject path
+/// has one entry point instead of reaching for a different method per event.
+pub enum ApprovalEvent<'a> {
+    /// A new request was just inserted and is now pending a decision.
+    Requested(&'a ApprovalRequest),
+    /// A pending request was approved or rejected.
+    Decided { request: &'a ApprovalRequest, approved: bool },
+}
+
+/// Dispatches approval notifications across configured channels, deduping
+/// identical in-flight requests so repeated autonomous attempts don't spam
+/// the operator.
+pub struct Notifier {
+    channels: Vec<Box<dyn NotificationChannel>>,
+    cooldown: Duration,
+    /// Dedupe key -> last time a notification was sent for it.
+    recent: Mutex<HashMap<String, Instant>>,
+}
+
+impl Notifier {
+    pub fn new(channels: Vec<Box<dyn NotificationChannel>>, cooldown: Duration) -> Self {
+        Self {
+            channels,
+            cooldown,
+            recent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Dispatch any approval lifecycle event through every configured
+    /// channel. The single entry point callers along the request/decide
+    /// path should use, rather than picking between the more specific
+    /// `notify_approval_requested`/`notify_approval_decided` themselves.
+    pub fn notify(&self, event: ApprovalEvent) -> NotifyOutcome {
+        match event {
+            ApprovalEvent::Requested(request) => self.notify_approval_requested(request),
+            ApprovalEvent::Decided { request, approved } => self.notify_approval_decided(request, approved),
+        }
+    }
+
+    /// Notify about a newly pending `ApprovalRequest`, suppressing the send
+    /// if an identical request was already notified within the cooldown
+    /// window and has not been decided since.
+    pub fn notify_approval_requested(&self, request: &ApprovalRequest) -> NotifyOutcome {
+        let key = dedupe_key(&request.agent_id, &request.action_type, &request.action_details);
+
+        {
+            let mut recent = self.recent.lock().unwrap();
+            if let Some(last_sent) = recent.get(&key) {
+                if last_sent.elapsed() < self.cooldown {
+                    return NotifyOutcome::Suppressed;
+                }
+            }
+            recent.insert(key, Instant::now());
+        }
+
+        let message = format!(
+            "[{}] {} requested by agent {} needs approval (request {})",
+            request.risk_level, request.action_type, request.agent_id, request.id
+        );
+        for channel in &self.channels {
+            let _ = channel.send(&message);
+        }
+        NotifyOutcome::Sent
+    }
+
+    /// Notify that `request` was approved or rejected. Unlike a fresh
+    /// request, a decision is never deduped/suppressed — each one is a
+    /// distinct, final event an operator needs to see. The decision also
+    /// clears the request's dedupe entry, so a later identical request
+    /// notifies again.
+    pub fn notify_approval_decided(&self, request: &ApprovalRequest, approved: bool) -> NotifyOutcome {
+        let key = dedupe_key(&request.agent_id, &request.action_type, &request.action_details);
+        self.recent.lock().unwrap().remove(&key);
+        let decision = if approved { "approved" } else { "rejected" };
+        let message = format!(
+            "[{}] {} on agent {} was {} (request {})",
+            request.risk_level, request.action_type, request.agent_id, decision, request.id
+        );
+        for channel in &self.channels {
+            let _ = channel.send(&message);
+        }
+        NotifyOutcome::Sent
+    }
+
+    /// Build the `ActivityLogEntry` recording a dispatch or suppression so
+    /// the decision is auditable even though no SQL table tracks it.
+    pub fn activity_entry(&self, outcome: &NotifyOutcome, request: &ApprovalRequest) -> ActivityLogEntry {
+        let (action, details) = match outcome {
+            NotifyOutcome::Sent => (
+                "approval_notification_sent",
+                format!(
+                    "Notified {} channel(s) for {} ({})",
+                    self.channels.len(),
+                    request.action_type,
+                    request.risk_level
+                ),
+            ),
+            NotifyOutcome::Suppressed => (
+                "approval_notification_suppressed",
+                format!(
+                    "Duplicate pending request for {} on agent {} within cooldown",
+                    request.action_type, request.agent_id
+                ),
+            ),
+        };
+
+        ActivityLogEntry {
+            id: Uuid::new_v4().to_string(),
+            agent_id: Some(request.agent_id.clone()),
+            task_id: request.task_id.clone(),
+            action: action.to_string(),
+            details: Some(details),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Hash `(agent_id, action_type, canonicalized action_details)` so
+/// structurally-identical requests (regardless of JSON key order) collapse
+/// to the same dedupe key.
+fn dedupe_key(agent_id: &str, action_type: &str, action_details: &Value) -> String {
+    let canonical = canonicalize(action_details).to_string();
+    let mut hasher = Sha256::new();
+    hasher.update(agent_id.as_bytes());
+    hasher.update(b"\0");
+    hasher.update(action_type.as_bytes());
+    hasher.update(b"\0");
+    hasher.update(canonical.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Recursively sort object keys so two JSON values that differ only in key
+/// order hash identically.
+fn canonicalize(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let sorted: BTreeMap<String, Value> =
+                map.iter().map(|(k, v)| (k.clone(), canonicalize(v))).collect();
+            serde_json::to_value(sorted).unwrap_or(Value::Null)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonicalize).collect()),
+        other => other.clone(),
+    }
+}
@@ -0,0 +1,341 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::scheduler::Schedule;
+
+/// Prefix every generated native scheduler entry is labeled/named with, so
+/// `uninstall` can find and remove them by `schedule_id` alone
+/// without needing the original `Schedule` back.
+const LABEL_PREFIX: &str = "com.agentforge.schedule";
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(PathBuf::from)
+}
+
+/// Expand one 5-field cron component (`*`, `*/N`, `A-B`, or a `,`-separated
+/// list of those) into every concrete value it matches. Returns `None` for
+/// a bare `*`, meaning "unconstrained" — callers should omit the
+/// corresponding key entirely rather than enumerate every possible value.
+fn expand_field(field: &str, min: u32, max: u32) -> Option<Vec<u32>> {
+    if field == "*" {
+        return None;
+    }
+    let mut values = Vec::new();
+    for part in field.split(',') {
+        if let Some(step) = part.strip_prefix("*/") {
+            let step: u32 = step.parse().ok()?;
+            let mut v = min;
+            while v <= max {
+                values.push(v);
+                v += step.max(1);
+            }
+        } else if let Some((a, b)) = part.split_once('-') {
+            values.extend(a.parse().ok()?..=b.parse().ok()?);
+        } else {
+            values.push(part.parse().ok()?);
+        }
+    }
+    values.sort_unstable();
+    values.dedup();
+    Some(values)
+}
+
+/// A single launchd `StartCalendarInterval` entry: any key left `None` is
+/// omitted from the dictionary, meaning "any value" for that field.
+#[derive(Debug, Clone, Default)]
+struct CalendarInterval {
+    minute: Option<u32>,
+    hour: Option<u32>,
+    day: Option<u32>,
+    month: Option<u32>,
+    weekday: Option<u32>,
+}
+
+/// Translate a 5-field `cron_expr` into the `StartCalendarInterval` entries
+/// launchd needs — one dict per concrete combination of constrained fields,
+/// since launchd has no step/interval primitive of its own and ORs across
+/// an array of dicts. To keep the plist bounded, the cross product is
+/// capped at 366 entries (a bare `day-of-month`/`month` sweep); anything
+/// wider falls back to a single entry holding the first value of each
+/// constrained field, which undershoots but never overshoots how often the
+/// job fires.
+fn cron_to_calendar_intervals(cron_expr: &str) -> Vec<CalendarInterval> {
+    let fields: Vec<&str> = cron_expr.split_whitespace().collect();
+    if fields.len() != 5 {
+        return Vec::new();
+    }
+    let minutes = expand_field(fields[0], 0, 59);
+    let hours = expand_field(fields[1], 0, 23);
+    let days = expand_field(fields[2], 1, 31);
+    let months = expand_field(fields[3], 1, 12);
+    let weekdays = expand_field(fields[4], 0, 6);
+
+    let combo_count = [&minutes, &hours, &days, &months, &weekdays]
+        .iter()
+        .map(|f| f.as_ref().map(Vec::len).unwrap_or(1))
+        .product::<usize>();
+
+    if combo_count > 366 {
+        return vec![CalendarInterval {
+            minute: minutes.and_then(|v| v.first().copied()),
+            hour: hours.and_then(|v| v.first().copied()),
+            day: days.and_then(|v| v.first().copied()),
+            month: months.and_then(|v| v.first().copied()),
+            weekday: weekdays.and_then(|v| v.first().copied()),
+        }];
+    }
+
+    let mut intervals = vec![CalendarInterval::default()];
+    for (field, set) in [(0, minutes), (1, hours), (2, days), (3, months), (4, weekdays)] {
+        let Some(values) = set else { continue };
+        intervals = intervals
+            .iter()
+            .flat_map(|base| {
+                values.iter().map(move |&v| {
+                    let mut next = base.clone();
+                    match field {
+                        0 => next.minute = Some(v),
+                        1 => next.hour = Some(v),
+                        2 => next.day = Some(v),
+                        3 => next.month = Some(v),
+                        _ => next.weekday = Some(v),
+                    }
+                    next
+                })
+            })
+            .collect();
+    }
+    intervals
+}
+
+fn calendar_interval_xml(interval: &CalendarInterval) -> String {
+    let mut entries = Vec::new();
+    if let Some(m) = interval.minute {
+        entries.push(format!("<key>Minute</key><integer>{m}</integer>"));
+    }
+    if let Some(h) = interval.hour {
+        entries.push(format!("<key>Hour</key><integer>{h}</integer>"));
+    }
+    if let Some(d) = interval.day {
+        entries.push(format!("<key>Day</key><integer>{d}</integer>"));
+    }
+    if let Some(m) = interval.month {
+        entries.push(format!("<key>Month</key><integer>{m}</integer>"));
+    }
+    if let Some(w) = interval.weekday {
+        entries.push(format!("<key>Weekday</key><integer>{w}</integer>"));
+    }
+    format!("<dict>{}</dict>", entries.join(""))
+}
+
+/// A macOS LaunchAgent plist that runs `binary_path --config --schedule
+/// <schedule.id>` on the schedule's recurrence (or once, for a one-time
+/// `run_at` schedule, via `StartInterval` computed from the gap to now —
+/// macOS has no native "run once at timestamp" key, so callers should
+/// `uninstall` right after it fires).
+pub fn render_launchd_plist(schedule: &Schedule, binary_path: &str) -> String {
+    let label = format!("{LABEL_PREFIX}.{}", schedule.id);
+    let start_calendar = match &schedule.cron_expr {
+        Some(cron_expr) => {
+            let intervals = cron_to_calendar_intervals(cron_expr);
+            format!(
+                "<key>StartCalendarInterval</key><array>{}</array>",
+                intervals.iter().map(calendar_interval_xml).collect::<Vec<_>>().join("")
+            )
+        }
+        None => schedule
+            .run_at
+            .as_deref()
+            .and_then(|run_at| chrono::DateTime::parse_from_rfc3339(run_at).ok())
+            .map(|run_at| {
+                let secs = (run_at.with_timezone(&chrono::Utc) - chrono::Utc::now()).num_seconds().max(1);
+                format!("<key>StartInterval</key><integer>{secs}</integer>")
+            })
+            .unwrap_or_default(),
+    };
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key><string>{label}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{binary_path}</string>
+        <string>--config</string>
+        <string>--schedule</string>
+        <string>{schedule_id}</string>
+    </array>
+    {start_calendar}
+    <key>RunAtLoad</key><false/>
+</dict>
+</plist>
+"#,
+        schedule_id = schedule.id,
+    )
+}
+
+fn launchd_plist_path(schedule_id: &str) -> Option<PathBuf> {
+    home_dir().map(|home| home.join("Library/LaunchAgents").join(format!("{LABEL_PREFIX}.{schedule_id}.plist")))
+}
+
+/// Translate a 5-field cron expression into a systemd `OnCalendar=` value
+/// (systemd's calendar-event syntax, distinct from cron's), e.g.
+/// `"0 9 * * 1"` -> `"Mon *-*-* 9:0:00"`.
+fn cron_to_on_calendar(cron_expr: &str) -> Option<String> {
+    let fields: Vec<&str> = cron_expr.split_whitespace().collect();
+    if fields.len() != 5 {
+        return None;
+    }
+    const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+    let weekday_part = if fields[4] == "*" {
+        "*".to_string()
+    } else {
+        expand_field(fields[4], 0, 6)?
+            .iter()
+            .map(|&d| WEEKDAYS[d as usize % 7])
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let minute = if fields[0].contains('/') { fields[0].replace("*/", "0/") } else { fields[0].to_string() };
+    let hour = if fields[1].contains('/') { fields[1].replace("*/", "0/") } else { fields[1].to_string() };
+    let day = fields[2];
+    let month = fields[3];
+    Some(format!("{weekday_part} *-{month}-{day} {hour}:{minute}:00"))
+}
+
+/// A `systemd --user` service+timer pair that runs `binary_path --config
+/// --schedule <schedule.id>` on the translated `OnCalendar=` recurrence.
+pub fn render_systemd_units(schedule: &Schedule, binary_path: &str) -> (String, String) {
+    let service = format!(
+        "[Unit]\nDescription=AgentForge schedule {name}\n\n[Service]\nType=oneshot\nExecStart={binary_path} --config --schedule {id}\n",
+        name = schedule.name,
+        id = schedule.id,
+    );
+
+    let on_calendar = match &schedule.cron_expr {
+        Some(cron_expr) => cron_to_on_calendar(cron_expr).unwrap_or_else(|| "*-*-* *:*:00".to_string()),
+        None => schedule.run_at.clone().unwrap_or_default(),
+    };
+    let timer = format!(
+        "[Unit]\nDescription=Timer for AgentForge schedule {name}\n\n[Timer]\nOnCalendar={on_calendar}\nPersistent=true\n\n[Install]\nWantedBy=timers.target\n",
+        name = schedule.name,
+    );
+
+    (service, timer)
+}
+
+fn systemd_unit_dir() -> Option<PathBuf> {
+    home_dir().map(|home| home.join(".config/systemd/user"))
+}
+
+/// The `schtasks /create` command line that registers `binary_path
+/// --config --schedule <schedule.id>` on Windows Task Scheduler, using
+/// `/sc` + `/mo`/`/d` to approximate the cron recurrence (Task Scheduler's
+/// triggers are coarser than cron — weekly/daily/hourly/minute only).
+pub fn render_schtasks_command(schedule: &Schedule, binary_path: &str) -> String {
+    let task_name = format!("{LABEL_PREFIX}.{}", schedule.id);
+    let run_command = format!("{binary_path} --config --schedule {}", schedule.id);
+
+    let schedule_args = match &schedule.cron_expr {
+        Some(cron_expr) => {
+            let fields: Vec<&str> = cron_expr.split_whitespace().collect();
+            if fields.len() == 5 && fields[4] != "*" {
+                "/sc WEEKLY /d MON,TUE,WED,THU,FRI,SAT,SUN".to_string()
+            } else if fields.len() == 5 && fields[0].starts_with("*/") {
+                format!("/sc MINUTE /mo {}", fields[0].trim_start_matches("*/"))
+            } else if fields.len() == 5 && fields[1].starts_with("*/") {
+                format!("/sc HOURLY /mo {}", fields[1].trim_start_matches("*/"))
+            } else {
+                "/sc DAILY".to_string()
+            }
+        }
+        None => "/sc ONCE".to_string(),
+    };
+
+    format!(r#"schtasks /create /tn "{task_name}" /tr "{run_command}" {schedule_args} /f"#)
+}
+
+/// Materialize `schedule` as a native scheduler entry for the current
+/// platform, so it fires even while the app isn't running.
+pub fn install(schedule: &Schedule, binary_path: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        let path = launchd_plist_path(&schedule.id).ok_or("could not resolve home directory")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("failed to create LaunchAgents dir: {e}"))?;
+        }
+        fs::write(&path, render_launchd_plist(schedule, binary_path))
+            .map_err(|e| format!("failed to write plist: {e}"))?;
+        let _ = Command::new("launchctl").arg("load").arg(&path).status();
+        return Ok(());
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let dir = systemd_unit_dir().ok_or("could not resolve home directory")?;
+        fs::create_dir_all(&dir).map_err(|e| format!("failed to create systemd user dir: {e}"))?;
+        let unit_name = format!("{LABEL_PREFIX}.{}", schedule.id);
+        let (service, timer) = render_systemd_units(schedule, binary_path);
+        fs::write(dir.join(format!("{unit_name}.service")), service)
+            .map_err(|e| format!("failed to write service unit: {e}"))?;
+        fs::write(dir.join(format!("{unit_name}.timer")), timer)
+            .map_err(|e| format!("failed to write timer unit: {e}"))?;
+        let _ = Command::new("systemctl").args(["--user", "daemon-reload"]).status();
+        let _ = Command::new("systemctl").args(["--user", "enable", "--now", &format!("{unit_name}.timer")]).status();
+        return Ok(());
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let command = render_schtasks_command(schedule, binary_path);
+        let status = Command::new("cmd").args(["/C", &command]).status();
+        return match status {
+            Ok(s) if s.success() => Ok(()),
+            Ok(s) => Err(format!("schtasks exited with status {s}")),
+            Err(e) => Err(format!("failed to run schtasks: {e}")),
+        };
+    }
+
+    #[allow(unreachable_code)]
+    Err("unsupported platform for OS-native scheduling".to_string())
+}
+
+/// Remove whatever native scheduler entry `install` created for
+/// `schedule_id`, identified by the deterministic `{LABEL_PREFIX}.<id>`
+/// name so this works without the original `Schedule` in hand.
+pub fn uninstall(schedule_id: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        if let Some(path) = launchd_plist_path(schedule_id) {
+            let _ = Command::new("launchctl").arg("unload").arg(&path).status();
+            let _ = fs::remove_file(&path);
+        }
+        return Ok(());
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let unit_name = format!("{LABEL_PREFIX}.{schedule_id}");
+        let _ = Command::new("systemctl").args(["--user", "disable", "--now", &format!("{unit_name}.timer")]).status();
+        if let Some(dir) = systemd_unit_dir() {
+            let _ = fs::remove_file(dir.join(format!("{unit_name}.service")));
+            let _ = fs::remove_file(dir.join(format!("{unit_name}.timer")));
+        }
+        let _ = Command::new("systemctl").args(["--user", "daemon-reload"]).status();
+        return Ok(());
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let task_name = format!("{LABEL_PREFIX}.{schedule_id}");
+        let _ = Command::new("schtasks").args(["/delete", "/tn", &task_name, "/f"]).status();
+        return Ok(());
+    }
+
+    #[allow(unreachable_code)]
+    Ok(())
+}
+
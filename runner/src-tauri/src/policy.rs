@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tasks::RiskLevel;
+
+/// What a matched (or default) policy rule decides for an action, before it
+/// would otherwise become a pending `approval_requests` row.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyOutcome {
+    AutoApprove,
+    AutoReject,
+    RequireHuman,
+}
+
+/// One ordered rule in a `Policy`. Every field left `None` matches any
+/// value for that dimension, so e.g. `{ action_type: Some("web_search"),
+/// agent_id: None, risk_level: None, outcome: AutoApprove }` auto-approves
+/// that action type regardless of which agent or risk level produced it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRule {
+    pub id: String,
+    pub action_type: Option<String>,
+    pub agent_id: Option<String>,
+    pub risk_level: Option<String>,
+    pub outcome: PolicyOutcome,
+}
+
+impl PolicyRule {
+    fn matches(&self, action_type: &str, agent_id: &str, risk_level: &str) -> bool {
+        self.action_type.as_deref().map_or(true, |v| v == action_type)
+            && self.agent_id.as_deref().map_or(true, |v| v == agent_id)
+            && self.risk_level.as_deref().map_or(true, |v| v == risk_level)
+    }
+}
+
+/// An ordered list of rules, evaluated before a request ever reaches the
+/// `approval_requests` table, plus the fallbacks that apply when no rule
+/// matches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Policy {
+    pub rules: Vec<PolicyRule>,
+    /// Applied when no rule in `rules` matches.
+    pub default_outcome: PolicyOutcome,
+    /// Overrides every rule and the default: any action at or above this
+    /// risk level always requires a human, regardless of what matched.
+    pub require_human_above: Option<RiskLevel>,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            default_outcome: PolicyOutcome::RequireHuman,
+            require_human_above: None,
+        }
+    }
+}
+
+/// The result of evaluating a `Policy` against one action: the `outcome` to
+/// act on, and `decided_by` — `None` for `RequireHuman` (no automated
+/// decision was made), or `Some("policy:<rule-id>")` / `Some("policy:default")`
+/// identifying which part of the policy produced an automated one, for the
+/// audit trail.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyDecision {
+    pub outcome: PolicyOutcome,
+    pub decided_by: Option<String>,
+}
+
+impl Policy {
+    /// Evaluate the first matching rule, in order; fall back to
+    /// `default_outcome` if none match. `require_human_above`, if set,
+    /// takes precedence over both — it guarantees dangerous actions always
+    /// block on a human even if a rule or the default would auto-decide.
+    pub fn evaluate(&self, action_type: &str, agent_id: &str, risk_level: RiskLevel) -> PolicyDecision {
+        if let Some(threshold) = &self.require_human_above {
+            if risk_level_rank(&risk_level) >= risk_level_rank(threshold) {
+                return PolicyDecision {
+                    outcome: PolicyOutcome::RequireHuman,
+                    decided_by: None,
+                };
+            }
+        }
+
+        let risk_str = serde_json::to_value(&risk_level)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+
+        if let Some(rule) = self.rules.iter().find(|rule| rule.matches(action_type, agent_id, &risk_str)) {
+            return match rule.outcome {
+                PolicyOutcome::RequireHuman => PolicyDecision { outcome: PolicyOutcome::RequireHuman, decided_by: None },
+                outcome => PolicyDecision { outcome, decided_by: Some(format!("policy:{}", rule.id)) },
+            };
+        }
+
+        match self.default_outcome {
+            PolicyOutcome::RequireHuman => PolicyDecision { outcome: PolicyOutcome::RequireHuman, decided_by: None },
+            outcome => PolicyDecision { outcome, decided_by: Some("policy:default".to_string()) },
+        }
+    }
+}
+
+/// Ordinal rank used to compare `RiskLevel`s for the `require_human_above`
+/// threshold, since the enum itself doesn't derive `Ord`.
+fn risk_level_rank(level: &RiskLevel) -> u8 {
+    match level {
+        RiskLevel::Low => 0,
+        RiskLevel::Medium => 1,
+        RiskLevel::High => 2,
+        RiskLevel::Critical => 3,
+    }
+}
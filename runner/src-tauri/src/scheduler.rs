@@ -1,6 +1,12 @@
+use chrono::{DateTime, Utc};
+use cron::Schedule as CronSchedule;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 
 /// A schedule for recurring or one-time tasks
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +22,16 @@ pub struct Schedule {
     pub last_run: Option<String>,
     pub next_run: Option<String>,
     pub created_at: String,
+    /// IANA zone (e.g. `"America/New_York"`) the cron fields are matched
+    /// against for local wall-clock time, so "every weekday at 5pm" means
+    /// 5pm there rather than 5pm UTC. `None` matches in UTC.
+    pub timezone: Option<String>,
+    /// When a window was missed entirely (app closed through it), whether
+    /// to fire once for the most recently missed occurrence on next wake
+    /// (`true`, the default) or silently resync to the present without
+    /// running it (`false`). Honored by `Scheduler::get_due_schedules`;
+    /// `Database::claim_due_schedules` fires every due schedule regardless.
+    pub catch_up_missed: bool,
 }
 
 /// Natural language schedule patterns
@@ -197,6 +213,76 @@ pub fn get_schedule_templates() -> Vec<ScheduleTemplate> {
     ]
 }
 
+/// Compute the next time `cron_expr` fires strictly after `after`. The
+/// `cron` crate parses 6-field expressions with a leading seconds field,
+/// while our `cron_expr` strings (produced by `NaturalLanguageParser` and
+/// stored on `Schedule`) are standard 5-field unix-cron, so a fixed "0"
+/// seconds field is prepended before parsing. Returns `None` if the
+/// expression is invalid or has no future occurrence.
+pub fn compute_next_run(cron_expr: &str, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let with_seconds = format!("0 {cron_expr}");
+    CronSchedule::from_str(&with_seconds).ok()?.after(&after).next()
+}
+
+/// Like `compute_next_run`, but matches the cron fields against local
+/// wall-clock time in `timezone` (an IANA zone name) when set, converting
+/// the result back to UTC. Falls back to plain UTC matching — and to
+/// `compute_next_run`'s behavior — when `timezone` is `None` or invalid.
+pub fn compute_next_run_tz(cron_expr: &str, after: DateTime<Utc>, timezone: Option<&str>) -> Option<DateTime<Utc>> {
+    let Some(tz_name) = timezone else {
+        return compute_next_run(cron_expr, after);
+    };
+    let Ok(tz) = tz_name.parse::<chrono_tz::Tz>() else {
+        return compute_next_run(cron_expr, after);
+    };
+    let with_seconds = format!("0 {cron_expr}");
+    let local_after = after.with_timezone(&tz);
+    let next_local = CronSchedule::from_str(&with_seconds).ok()?.after(&local_after).next()?;
+    Some(next_local.with_timezone(&Utc))
+}
+
+fn parse_rfc3339(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s).ok().map(|t| t.with_timezone(&Utc))
+}
+
+/// The schedule's next fire time computed from its current state:
+/// `last_run` (falling back to `created_at`) for recurring cron schedules,
+/// or `run_at` itself for a one-time schedule that hasn't run yet. `None`
+/// once a one-time schedule has already run, or if `cron_expr` fails to
+/// parse.
+fn next_fire(schedule: &Schedule, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let Some(cron_expr) = &schedule.cron_expr else {
+        if schedule.last_run.is_some() {
+            return None;
+        }
+        return schedule.run_at.as_deref().and_then(parse_rfc3339);
+    };
+    let reference = schedule
+        .last_run
+        .as_deref()
+        .or(Some(schedule.created_at.as_str()))
+        .and_then(parse_rfc3339)
+        .unwrap_or(now);
+    compute_next_run_tz(cron_expr, reference, schedule.timezone.as_deref())
+}
+
+/// A schedule is due once its computed `next_fire` time has arrived.
+pub fn is_due(schedule: &Schedule, now: DateTime<Utc>) -> bool {
+    next_fire(schedule, now).map(|t| t <= now).unwrap_or(false)
+}
+
+/// True when more than one cron period has elapsed since `due_at` was due
+/// — i.e. the window was missed entirely (the app was closed through it)
+/// rather than the schedule having just now become due. One-time
+/// (`run_at`-only) schedules are never considered stale: there's only ever
+/// one occurrence to miss.
+fn is_stale(schedule: &Schedule, now: DateTime<Utc>, due_at: DateTime<Utc>) -> bool {
+    let Some(cron_expr) = &schedule.cron_expr else { return false };
+    compute_next_run_tz(cron_expr, due_at, schedule.timezone.as_deref())
+        .map(|following| following <= now)
+        .unwrap_or(false)
+}
+
 /// Scheduler state manager
 pub struct Scheduler {
     active_schedules: HashMap<String, Schedule>,
@@ -208,20 +294,69 @@ impl Scheduler {
             active_schedules: HashMap::new(),
         }
     }
-    
+
     pub fn add_schedule(&mut self, schedule: Schedule) {
         if schedule.enabled {
             self.active_schedules.insert(schedule.id.clone(), schedule);
         }
     }
-    
+
     pub fn remove_schedule(&mut self, schedule_id: &str) {
         self.active_schedules.remove(schedule_id);
     }
-    
-    pub fn get_due_schedules(&self, now: &chrono::DateTime<chrono::Utc>) -> Vec<&Schedule> {
-        // This would check cron expressions against current time
-        // For now, return empty - full implementation would use cron crate
-        vec![]
+
+    /// Schedules ready to fire right now: due, and either on-time or opted
+    /// into catching up on a missed window.
+    pub fn get_due_schedules(&self, now: &DateTime<Utc>) -> Vec<&Schedule> {
+        self.active_schedules
+            .values()
+            .filter(|schedule| match next_fire(schedule, *now) {
+                Some(due_at) if due_at <= *now => schedule.catch_up_missed || !is_stale(schedule, *now, due_at),
+                _ => false,
+            })
+            .collect()
     }
+
+    /// Schedules whose window was missed entirely and which opted out of
+    /// catch-up (`catch_up_missed == false`): not included in
+    /// `get_due_schedules`, but the caller should still resync `last_run`
+    /// to `now` for these so they resume on their normal cadence instead of
+    /// firing once for a stale slot.
+    pub fn get_stale_schedules_to_skip(&self, now: &DateTime<Utc>) -> Vec<&Schedule> {
+        self.active_schedules
+            .values()
+            .filter(|schedule| match next_fire(schedule, *now) {
+                Some(due_at) if due_at <= *now => !schedule.catch_up_missed && is_stale(schedule, *now, due_at),
+                _ => false,
+            })
+            .collect()
+    }
+}
+
+/// Spawn a minute-granularity background thread that polls `scheduler` for
+/// due schedules and hands each one to `dispatch` (e.g. POSTing
+/// `task_type`/`task_input` to the Python backend), then calls `on_fired`
+/// so the caller can persist the updated `last_run`/`next_run`. Schedules
+/// that missed their window without opting into catch-up are passed to
+/// `on_skipped` instead, to be resynced without firing. Returns the thread's
+/// `JoinHandle`; the loop runs until the process exits.
+pub fn spawn_tick_loop(
+    scheduler: Arc<Mutex<Scheduler>>,
+    dispatch: impl Fn(&Schedule) + Send + 'static,
+    on_fired: impl Fn(&Schedule, DateTime<Utc>) + Send + 'static,
+    on_skipped: impl Fn(&Schedule, DateTime<Utc>) + Send + 'static,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || loop {
+        let now = Utc::now();
+        if let Ok(guard) = scheduler.lock() {
+            for schedule in guard.get_due_schedules(&now) {
+                dispatch(schedule);
+                on_fired(schedule, now);
+            }
+            for schedule in guard.get_stale_schedules_to_skip(&now) {
+                on_skipped(schedule, now);
+            }
+        }
+        thread::sleep(Duration::from_secs(60));
+    })
 }
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+
+use crate::tasks::{Task, TaskStats, TaskStatus};
+
+/// How long a completed or cancelled task's spool file is kept on disk
+/// before it's pruned, giving operators a window to inspect it. Failed
+/// tasks' files are not pruned.
+const DEFAULT_RETENTION: chrono::Duration = chrono::Duration::hours(24);
+
+/// Durable on-disk mirror of the in-memory task queue. Every task is
+/// serialized to its own `<id>.json` file under the spool directory so a
+/// crash doesn't lose pending/scheduled/running work; `recover()` rebuilds
+/// the in-memory index from those files on startup.
+pub struct TaskSpool {
+    dir: PathBuf,
+    retention: chrono::Duration,
+    index: Mutex<HashMap<String, Task>>,
+}
+
+impl TaskSpool {
+    pub fn open(dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let spool = Self {
+            dir,
+            retention: DEFAULT_RETENTION,
+            index: Mutex::new(HashMap::new()),
+        };
+        spool.recover()?;
+        Ok(spool)
+    }
+
+    pub fn with_retention(mut self, retention: chrono::Duration) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    fn path_for(&self, task_id: &str) -> PathBuf {
+        self.dir.join(format!("{task_id}.json"))
+    }
+
+    /// Serialize `task` to its spool file via a write-then-rename so a crash
+    /// mid-write can never leave a half-written file behind, and fsync
+    /// before returning so the write survives a crash right after this call.
+    fn persist(&self, task: &Task) -> std::io::Result<()> {
+        let path = self.path_for(&task.id);
+        let tmp_path = path.with_extension("json.tmp");
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(&serde_json::to_vec_pretty(task)?)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    /// Persist a task's current state (on creation and on every status
+    /// change) and update the in-memory index used for fast stats.
+    pub fn save(&self, task: Task) -> std::io::Result<()> {
+        self.persist(&task)?;
+        self.index.lock().unwrap().insert(task.id.clone(), task);
+        Ok(())
+    }
+
+    pub fn get(&self, task_id: &str) -> Option<Task> {
+        self.index.lock().unwrap().get(task_id).cloned()
+    }
+
+    pub fn remove(&self, task_id: &str) -> std::io::Result<()> {
+        let path = self.path_for(task_id);
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        self.index.lock().unwrap().remove(task_id);
+        Ok(())
+    }
+
+    /// Delete spool files for completed or cancelled tasks whose retention window has
+    /// elapsed. Intended to be called periodically (e.g. alongside the
+    /// scheduler tick).
+    pub fn sweep_expired(&self) -> std::io::Result<()> {
+        let now = Utc::now();
+        let expired: Vec<String> = {
+            let index = self.index.lock().unwrap();
+            index
+                .values()
+                .filter(|task| matches!(task.status.as_str(), "completed" | "cancelled"))
+                .filter(|task| {
+                    task.completed_at
+                        .as_deref()
+                        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+                        .map(|ts| now.signed_duration_since(ts.with_timezone(&Utc)) > self.retention)
+                        .unwrap_or(false)
+                })
+                .map(|task| task.id.clone())
+                .collect()
+        };
+
+        for task_id in expired {
+            self.remove(&task_id)?;
+        }
+        Ok(())
+    }
+
+    /// Rebuild the in-memory index from the spool directory, normalizing
+    /// crash-time state: a task found `Running` goes back to `Pending` (or
+    /// `Failed` if it had already exhausted its retries), and a `Scheduled`
+    /// task whose `scheduled_at` has already passed is re-queued as
+    /// `Pending` so it isn't silently skipped.
+    fn recover(&self) -> std::io::Result<()> {
+        let now = Utc::now();
+        let mut recovered = Vec::new();
+
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+
+            let Ok(contents) = fs::read_to_string(&path) else { continue };
+            let Ok(mut task) = serde_json::from_str::<Task>(&contents) else { continue };
+
+            match task.status.as_str() {
+                "running" => {
+                    task.status = if task.retry_count >= task.max_retries {
+                        TaskStatus::Failed.to_string()
+                    } else {
+                        TaskStatus::Pending.to_string()
+                    };
+                }
+                "scheduled" => {
+                    let is_due = task
+                        .scheduled_at
+                        .as_deref()
+                        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+                        .map(|ts| ts.with_timezone(&Utc) <= now)
+                        .unwrap_or(false);
+                    if is_due {
+                        task.status = TaskStatus::Pending.to_string();
+                    }
+                }
+                _ => {}
+            }
+
+            recovered.push(task);
+        }
+
+        let mut index = self.index.lock().unwrap();
+        for task in &recovered {
+            index.insert(task.id.clone(), task.clone());
+        }
+        drop(index);
+
+        // Re-persist anything we just normalized so the on-disk state
+        // matches what we now believe is true.
+        for task in recovered {
+            self.persist(&task)?;
+        }
+        Ok(())
+    }
+
+    pub fn stats(&self) -> TaskStats {
+        let index = self.index.lock().unwrap();
+        let mut stats = TaskStats::default();
+        for task in index.values() {
+            stats.total += 1;
+            match task.status.as_str() {
+                "pending" => stats.pending += 1,
+                "scheduled" => stats.scheduled += 1,
+                "running" => stats.running += 1,
+                "completed" => stats.completed += 1,
+                "failed" => stats.failed += 1,
+                "cancelled" => stats.cancelled += 1,
+                _ => {}
+            }
+        }
+        stats
+    }
+}
@@ -26,6 +26,20 @@ impl std::fmt::Display for TaskStatus {
     }
 }
 
+impl TaskStatus {
+    pub fn parse(status: &str) -> Option<Self> {
+        match status {
+            "pending" => Some(TaskStatus::Pending),
+            "scheduled" => Some(TaskStatus::Scheduled),
+            "running" => Some(TaskStatus::Running),
+            "completed" => Some(TaskStatus::Completed),
+            "failed" => Some(TaskStatus::Failed),
+            "cancelled" => Some(TaskStatus::Cancelled),
+            _ => None,
+        }
+    }
+}
+
 /// A task to be executed by an agent
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
@@ -41,6 +55,26 @@ pub struct Task {
     pub completed_at: Option<String>,
     pub created_at: String,
     pub retry_count: u32,
+    /// Retries allowed for this specific task before it's dead-lettered.
+    /// Defaults to `TaskExecutionConfig::default().max_retries` so rows
+    /// written before this column existed behave the same as before.
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// When this task is next due to be retried after a failure, distinct
+    /// from `scheduled_at` (the original one-time schedule). `None` means
+    /// it isn't waiting on a retry.
+    #[serde(default)]
+    pub next_retry_at: Option<String>,
+    /// Start timestamps of attempts to run this task, oldest first, copied
+    /// into the dead-letter report by `backoff::evaluate_failure`. The
+    /// `tasks` table has no column for them and nothing appends to this
+    /// list, so a task loaded from `Database` carries an empty list.
+    #[serde(default)]
+    pub attempt_timestamps: Vec<String>,
+}
+
+pub fn default_max_retries() -> u32 {
+    TaskExecutionConfig::default().max_retries
 }
 
 /// Task statistics
@@ -53,6 +87,10 @@ pub struct TaskStats {
     pub completed: u32,
     pub failed: u32,
     pub cancelled: u32,
+    /// Failed tasks whose `retry_count` reached their `max_retries` (a
+    /// subset of `failed`). A task dead-lettered early by a non-retryable
+    /// error is counted in `failed` but not here.
+    pub dead: u32,
 }
 
 /// Activity log entry
@@ -66,6 +104,52 @@ pub struct ActivityLogEntry {
     pub timestamp: String,
 }
 
+/// Narrows a `get_activity` query. Every field is optional and additive
+/// (`None` leaves that dimension unfiltered), so `ActivityFilter::default()`
+/// returns the whole log subject only to `limit`.
+#[derive(Debug, Clone)]
+pub struct ActivityFilter {
+    pub agent_id: Option<String>,
+    pub task_id: Option<String>,
+    /// Matches `activity_log.action` by prefix, e.g. `"task_"` to see every
+    /// `task_created`/`task_completed`/... entry without enumerating them.
+    pub action_prefix: Option<String>,
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    pub limit: u32,
+}
+
+impl Default for ActivityFilter {
+    fn default() -> Self {
+        Self {
+            agent_id: None,
+            task_id: None,
+            action_prefix: None,
+            from: None,
+            to: None,
+            limit: 100,
+        }
+    }
+}
+
+/// The bucket width for `get_task_throughput`'s time-series grouping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    Hourly,
+    Daily,
+}
+
+impl Granularity {
+    /// The `strftime` format string that truncates a timestamp down to the
+    /// start of its bucket.
+    pub fn strftime_format(self) -> &'static str {
+        match self {
+            Granularity::Hourly => "%Y-%m-%dT%H:00:00",
+            Granularity::Daily => "%Y-%m-%d",
+        }
+    }
+}
+
 /// Approval request for autonomous actions
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApprovalRequest {
@@ -77,6 +161,40 @@ pub struct ApprovalRequest {
     pub risk_level: String,
     pub status: String,
     pub created_at: String,
+    /// When `status` moved off `pending`, i.e. when it was approved or
+    /// rejected. `None` while the request is still awaiting a decision.
+    pub decided_at: Option<String>,
+    /// Who or what made the decision: `"policy:<rule-id>"` / `"policy:default"`
+    /// for one the policy engine auto-decided, or `"human"` for one decided
+    /// through `process_approval`. `None` while still pending.
+    pub decided_by: Option<String>,
+}
+
+/// Narrows and paginates a `list_approvals` query. `fields`, when set,
+/// projects the SQL `SELECT` down to just those columns — `id` is always
+/// included regardless, and every `ApprovalRequest` field outside the
+/// projection comes back at its default rather than being fetched.
+#[derive(Debug, Clone)]
+pub struct ListApprovalsQuery {
+    pub status: Option<String>,
+    pub agent_id: Option<String>,
+    pub risk_level: Option<String>,
+    pub limit: u32,
+    pub offset: u32,
+    pub fields: Option<Vec<String>>,
+}
+
+impl Default for ListApprovalsQuery {
+    fn default() -> Self {
+        Self {
+            status: None,
+            agent_id: None,
+            risk_level: None,
+            limit: 50,
+            offset: 0,
+            fields: None,
+        }
+    }
 }
 
 /// Risk levels for actions
@@ -136,7 +254,10 @@ impl RiskLevel {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskExecutionConfig {
     pub max_retries: u32,
+    /// Base delay for the exponential backoff: `retry_delay_ms * 2^retry_count`.
     pub retry_delay_ms: u64,
+    /// Upper bound on the computed backoff delay before jitter is applied.
+    pub max_delay_ms: u64,
     pub timeout_ms: u64,
     pub dry_run: bool,
 }
@@ -146,6 +267,7 @@ impl Default for TaskExecutionConfig {
         Self {
             max_retries: 3,
             retry_delay_ms: 1000,
+            max_delay_ms: 60_000,
             timeout_ms: 60000,
             dry_run: false,
         }
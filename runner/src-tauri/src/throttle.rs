@@ -0,0 +1,149 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::tasks::ActivityLogEntry;
+
+/// Per-agent (optionally per-`task_type`) concurrency and rate limits.
+#[derive(Debug, Clone)]
+pub struct ThrottleLimits {
+    /// Maximum number of tasks this agent may have `Running` at once.
+    pub max_concurrent: u32,
+    /// Maximum number of task starts allowed within `window`.
+    pub max_starts_per_window: u32,
+    pub window: Duration,
+}
+
+impl Default for ThrottleLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 5,
+            max_starts_per_window: 20,
+            window: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Outcome of checking a task against its agent's throttle limits.
+pub enum ThrottleDecision {
+    Allow,
+    /// The task may not start now. `scheduled_at` is a later start time: one
+    /// `window` ahead when the concurrency limit is hit, or when the oldest
+    /// start leaves the window when the rate limit is hit. The runner's
+    /// `claim_next_task` command puts a held task back to `pending` and does
+    /// not use `scheduled_at`.
+    Hold {
+        scheduled_at: String,
+        reason: String,
+    },
+}
+
+#[derive(Default)]
+struct AgentCounters {
+    in_flight: u32,
+    /// Timestamps of starts still inside the current sliding window.
+    starts: VecDeque<SystemTime>,
+}
+
+/// Keyed set of in-flight counters and sliding-window start timestamps that
+/// stop a single agent from saturating the runner.
+#[derive(Default)]
+pub struct Throttle {
+    limits: Mutex<HashMap<String, ThrottleLimits>>,
+    counters: Mutex<HashMap<String, AgentCounters>>,
+}
+
+impl Throttle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Configure limits for `agent_id`, or for `agent_id:task_type` when a
+    /// narrower override is needed.
+    pub fn set_limits(&self, key: &str, limits: ThrottleLimits) {
+        self.limits.lock().unwrap().insert(key.to_string(), limits);
+    }
+
+    fn limits_for(&self, agent_id: &str, task_type: &str) -> ThrottleLimits {
+        let limits = self.limits.lock().unwrap();
+        limits
+            .get(&format!("{agent_id}:{task_type}"))
+            .or_else(|| limits.get(agent_id))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Decide whether a task for `agent_id`/`task_type` may start now. On
+    /// `Allow`, the in-flight counter and start timestamp are recorded
+    /// immediately; call `release` once the task leaves `Running`.
+    pub fn check(&self, agent_id: &str, task_type: &str) -> ThrottleDecision {
+        let limits = self.limits_for(agent_id, task_type);
+        let now = SystemTime::now();
+
+        let mut counters = self.counters.lock().unwrap();
+        let entry = counters.entry(agent_id.to_string()).or_default();
+
+        while let Some(&oldest) = entry.starts.front() {
+            if now.duration_since(oldest).unwrap_or_default() > limits.window {
+                entry.starts.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if entry.in_flight >= limits.max_concurrent {
+            return ThrottleDecision::Hold {
+                scheduled_at: (Utc::now() + chrono_window(limits.window)).to_rfc3339(),
+                reason: format!(
+                    "concurrency limit ({}) reached for agent '{agent_id}'",
+                    limits.max_concurrent
+                ),
+            };
+        }
+
+        if entry.starts.len() as u32 >= limits.max_starts_per_window {
+            let oldest = *entry.starts.front().expect("checked len above");
+            let boundary = oldest + limits.window;
+            let wait = boundary.duration_since(now).unwrap_or_default();
+            return ThrottleDecision::Hold {
+                scheduled_at: (Utc::now() + chrono_window(wait)).to_rfc3339(),
+                reason: format!(
+                    "rate limit ({} starts / {:?}) reached for agent '{agent_id}'",
+                    limits.max_starts_per_window, limits.window
+                ),
+            };
+        }
+
+        entry.in_flight += 1;
+        entry.starts.push_back(now);
+        ThrottleDecision::Allow
+    }
+
+    /// Free the concurrency slot held by a task that just left `Running`.
+    pub fn release(&self, agent_id: &str) {
+        let mut counters = self.counters.lock().unwrap();
+        if let Some(entry) = counters.get_mut(agent_id) {
+            entry.in_flight = entry.in_flight.saturating_sub(1);
+        }
+    }
+}
+
+fn chrono_window(window: Duration) -> chrono::Duration {
+    chrono::Duration::from_std(window).unwrap_or(chrono::Duration::zero())
+}
+
+/// Build the `ActivityLogEntry` that records a throttling hold so operators
+/// can see why a task didn't start immediately.
+pub fn throttled_activity_entry(agent_id: &str, task_id: &str, reason: &str) -> ActivityLogEntry {
+    ActivityLogEntry {
+        id: Uuid::new_v4().to_string(),
+        agent_id: Some(agent_id.to_string()),
+        task_id: Some(task_id.to_string()),
+        action: "task_throttled".to_string(),
+        details: Some(reason.to_string()),
+        timestamp: Utc::now().to_rfc3339(),
+    }
+}
@@ -64,6 +64,102 @@ pub struct WebhookTriggerConfig {
     pub endpoint: String,       // The webhook URL path
     pub secret: Option<String>, // Optional secret for verification
     pub method: String,         // HTTP method (POST, GET)
+    /// Header carrying the HMAC signature, e.g. `X-Hub-Signature-256`.
+    /// Defaults to `X-Webhook-Signature` when unset.
+    #[serde(default)]
+    pub signature_header: Option<String>,
+}
+
+/// Why an inbound webhook request was rejected before it could fire its
+/// trigger.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WebhookVerificationError {
+    MethodMismatch { expected: String, actual: String },
+    MissingSignature,
+    InvalidSignatureFormat,
+    SignatureMismatch,
+}
+
+impl std::fmt::Display for WebhookVerificationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebhookVerificationError::MethodMismatch { expected, actual } => {
+                write!(f, "expected HTTP method {expected}, got {actual}")
+            }
+            WebhookVerificationError::MissingSignature => write!(f, "missing signature header"),
+            WebhookVerificationError::InvalidSignatureFormat => write!(f, "signature header is not valid hex"),
+            WebhookVerificationError::SignatureMismatch => write!(f, "signature does not match"),
+        }
+    }
+}
+
+impl WebhookTriggerConfig {
+    pub fn signature_header_name(&self) -> &str {
+        self.signature_header.as_deref().unwrap_or("X-Webhook-Signature")
+    }
+
+    /// Verify an inbound request against this trigger's configured HTTP
+    /// method and, when a `secret` is set, its HMAC-SHA256 signature (the
+    /// common `sha256=<hex>` convention is accepted as well as bare hex).
+    /// With no secret configured only the method is checked.
+    pub fn verify(
+        &self,
+        method: &str,
+        body: &[u8],
+        signature_header_value: Option<&str>,
+    ) -> Result<(), WebhookVerificationError> {
+        if !self.method.eq_ignore_ascii_case(method) {
+            return Err(WebhookVerificationError::MethodMismatch {
+                expected: self.method.clone(),
+                actual: method.to_string(),
+            });
+        }
+
+        let Some(secret) = &self.secret else {
+            return Ok(());
+        };
+
+        let header_value = signature_header_value.ok_or(WebhookVerificationError::MissingSignature)?;
+        let hex_sig = header_value.strip_prefix("sha256=").unwrap_or(header_value);
+        let provided = decode_hex(hex_sig).ok_or(WebhookVerificationError::InvalidSignatureFormat)?;
+
+        let expected = hmac_sha256(secret.as_bytes(), body);
+        if constant_time_eq(&expected, &provided) {
+            Ok(())
+        } else {
+            Err(WebhookVerificationError::SignatureMismatch)
+        }
+    }
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+fn decode_hex(input: &str) -> Option<Vec<u8>> {
+    let input = input.trim();
+    if input.len() % 2 != 0 {
+        return None;
+    }
+    (0..input.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&input[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Compare two byte slices without short-circuiting on the first mismatch,
+/// so the comparison time doesn't leak how much of the signature was
+/// correct.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
 }
 
 /// Trigger condition for filtering events
@@ -103,9 +199,16 @@ pub struct TriggerEvent {
 }
 
 impl TriggerEvent {
-    pub fn file_created(trigger_id: &str, path: &str) -> Self {
+    /// Build the event and publish it onto `bus` so SSE/WebSocket
+    /// subscribers learn about it as soon as it fires.
+    fn publish(self, bus: &crate::events::EventBus, agent_id: &str, task_type: &str) -> Self {
+        bus.publish_trigger(agent_id, task_type, self.clone());
+        self
+    }
+
+    pub fn file_created(bus: &crate::events::EventBus, trigger: &Trigger, path: &str) -> Self {
         Self {
-            trigger_id: trigger_id.to_string(),
+            trigger_id: trigger.id.clone(),
             trigger_type: "file_system".to_string(),
             timestamp: chrono::Utc::now().to_rfc3339(),
             data: serde_json::json!({
@@ -113,11 +216,12 @@ impl TriggerEvent {
                 "path": path,
             }),
         }
+        .publish(bus, &trigger.agent_id, &trigger.task_type)
     }
-    
-    pub fn file_modified(trigger_id: &str, path: &str) -> Self {
+
+    pub fn file_modified(bus: &crate::events::EventBus, trigger: &Trigger, path: &str) -> Self {
         Self {
-            trigger_id: trigger_id.to_string(),
+            trigger_id: trigger.id.clone(),
             trigger_type: "file_system".to_string(),
             timestamp: chrono::Utc::now().to_rfc3339(),
             data: serde_json::json!({
@@ -125,11 +229,12 @@ impl TriggerEvent {
                 "path": path,
             }),
         }
+        .publish(bus, &trigger.agent_id, &trigger.task_type)
     }
-    
-    pub fn email_received(trigger_id: &str, from: &str, subject: &str) -> Self {
+
+    pub fn email_received(bus: &crate::events::EventBus, trigger: &Trigger, from: &str, subject: &str) -> Self {
         Self {
-            trigger_id: trigger_id.to_string(),
+            trigger_id: trigger.id.clone(),
             trigger_type: "email".to_string(),
             timestamp: chrono::Utc::now().to_rfc3339(),
             data: serde_json::json!({
@@ -138,11 +243,12 @@ impl TriggerEvent {
                 "subject": subject,
             }),
         }
+        .publish(bus, &trigger.agent_id, &trigger.task_type)
     }
-    
-    pub fn webhook_received(trigger_id: &str, method: &str, body: Value) -> Self {
+
+    pub fn webhook_received(bus: &crate::events::EventBus, trigger: &Trigger, method: &str, body: Value) -> Self {
         Self {
-            trigger_id: trigger_id.to_string(),
+            trigger_id: trigger.id.clone(),
             trigger_type: "webhook".to_string(),
             timestamp: chrono::Utc::now().to_rfc3339(),
             data: serde_json::json!({
@@ -151,5 +257,37 @@ impl TriggerEvent {
                 "body": body,
             }),
         }
+        .publish(bus, &trigger.agent_id, &trigger.task_type)
+    }
+}
+
+/// Verify an inbound webhook request against `config` and, on success, fire
+/// `TriggerEvent::webhook_received` onto the bus. On failure, no event is
+/// published and the caller gets back an `ActivityLogEntry` documenting the
+/// rejected verification so it can be persisted.
+pub fn handle_webhook(
+    bus: &crate::events::EventBus,
+    trigger: &Trigger,
+    config: &WebhookTriggerConfig,
+    method: &str,
+    raw_body: &[u8],
+    signature_header_value: Option<&str>,
+) -> Result<TriggerEvent, (WebhookVerificationError, crate::tasks::ActivityLogEntry)> {
+    match config.verify(method, raw_body, signature_header_value) {
+        Ok(()) => {
+            let body_json: Value = serde_json::from_slice(raw_body).unwrap_or(Value::Null);
+            Ok(TriggerEvent::webhook_received(bus, trigger, method, body_json))
+        }
+        Err(err) => {
+            let entry = crate::tasks::ActivityLogEntry {
+                id: uuid::Uuid::new_v4().to_string(),
+                agent_id: Some(trigger.agent_id.clone()),
+                task_id: None,
+                action: "webhook_verification_failed".to_string(),
+                details: Some(format!("trigger '{}': {err}", trigger.name)),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            };
+            Err((err, entry))
+        }
     }
 }